@@ -26,6 +26,10 @@ fn main() -> Result<(), eframe::Error> {
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct Pane {
     nr: usize,
+
+    /// Set by the user by renaming the tab.
+    #[serde(default)]
+    title: Option<String>,
 }
 
 impl std::fmt::Debug for Pane {
@@ -36,7 +40,7 @@ impl std::fmt::Debug for Pane {
 
 impl Pane {
     pub fn with_nr(nr: usize) -> Self {
-        Self { nr }
+        Self { nr, title: None }
     }
 
     pub fn ui(&mut self, ui: &mut egui::Ui) -> egui_tiles::UiResponse {
@@ -58,6 +62,9 @@ struct TreeBehavior {
     simplification_options: egui_tiles::SimplificationOptions,
     tab_bar_height: f32,
     gap_width: f32,
+    layout_animation_time: f32,
+    pane_title_bars: bool,
+    drop_target_mode: egui_tiles::DropTargetMode,
     add_child_to: Option<egui_tiles::TileId>,
 }
 
@@ -67,6 +74,9 @@ impl Default for TreeBehavior {
             simplification_options: Default::default(),
             tab_bar_height: 24.0,
             gap_width: 2.0,
+            layout_animation_time: 0.0,
+            pane_title_bars: false,
+            drop_target_mode: Default::default(),
             add_child_to: None,
         }
     }
@@ -78,6 +88,9 @@ impl TreeBehavior {
             simplification_options,
             tab_bar_height,
             gap_width,
+            layout_animation_time,
+            pane_title_bars,
+            drop_target_mode,
             add_child_to: _,
         } = self;
 
@@ -110,6 +123,34 @@ impl TreeBehavior {
                         .speed(1.0),
                 );
                 ui.end_row();
+
+                ui.label("Layout animation time:");
+                ui.add(
+                    egui::DragValue::new(layout_animation_time)
+                        .clamp_range(0.0..=2.0)
+                        .speed(0.01)
+                        .suffix(" s"),
+                );
+                ui.end_row();
+
+                ui.label("Pane title bars:");
+                ui.checkbox(pane_title_bars, "");
+                ui.end_row();
+
+                ui.label("Drop targets:");
+                ui.horizontal(|ui| {
+                    ui.selectable_value(
+                        drop_target_mode,
+                        egui_tiles::DropTargetMode::Nearest,
+                        "Nearest",
+                    );
+                    ui.selectable_value(
+                        drop_target_mode,
+                        egui_tiles::DropTargetMode::Compass,
+                        "Compass",
+                    );
+                });
+                ui.end_row();
             });
     }
 }
@@ -125,7 +166,32 @@ impl egui_tiles::Behavior<Pane> for TreeBehavior {
     }
 
     fn tab_title_for_pane(&mut self, view: &Pane) -> egui::WidgetText {
-        format!("View {}", view.nr).into()
+        match &view.title {
+            Some(title) => title.clone().into(),
+            None => format!("View {}", view.nr).into(),
+        }
+    }
+
+    fn can_rename_tab(&self, tiles: &egui_tiles::Tiles<Pane>, tile_id: egui_tiles::TileId) -> bool {
+        matches!(tiles.get(tile_id), Some(egui_tiles::Tile::Pane(_)))
+    }
+
+    fn on_tab_renamed(
+        &mut self,
+        tiles: &mut egui_tiles::Tiles<Pane>,
+        tile_id: egui_tiles::TileId,
+        new_title: &str,
+    ) -> bool {
+        let new_title = new_title.trim();
+        if new_title.is_empty() {
+            return false;
+        }
+        if let Some(egui_tiles::Tile::Pane(pane)) = tiles.get_mut(tile_id) {
+            pane.title = Some(new_title.to_owned());
+            true
+        } else {
+            false
+        }
     }
 
     fn top_bar_left_ui(
@@ -177,6 +243,18 @@ impl egui_tiles::Behavior<Pane> for TreeBehavior {
     fn simplification_options(&self) -> egui_tiles::SimplificationOptions {
         self.simplification_options
     }
+
+    fn show_pane_title_bar(&self, _tile_id: egui_tiles::TileId, _pane: &Pane) -> bool {
+        self.pane_title_bars
+    }
+
+    fn layout_animation_time(&self) -> f32 {
+        self.layout_animation_time
+    }
+
+    fn drop_target_mode(&self) -> egui_tiles::DropTargetMode {
+        self.drop_target_mode
+    }
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
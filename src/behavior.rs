@@ -1,8 +1,12 @@
 use egui::{
-    vec2, Color32, Id, Rect, Response, Rgba, Sense, Stroke, TextStyle, Ui, Visuals, WidgetText,
+    vec2, Color32, Id, Rect, Response, Rgba, Sense, Stroke, TextStyle, Ui, Vec2, Visuals,
+    WidgetText,
 };
 
-use super::{ResizeState, SimplificationOptions, Tile, TileId, Tiles, UiResponse};
+use super::{
+    CompassButton, DragPayload, DropTargetMode, InsertionPoint, ResizeState, SimplificationMode,
+    SimplificationOptions, Tile, TileId, Tiles, UiResponse,
+};
 
 /// Trait defining how the [`super::Tree`] and its panes should be shown.
 pub trait Behavior<Pane> {
@@ -97,7 +101,153 @@ pub trait Behavior<Pane> {
     ) {
     }
 
+    /// Can the user rename the given tab?
+    ///
+    /// If `true`, double-clicking the tab (or pressing F2 while it is hovered or focused)
+    /// turns its title into a text edit. Enter or clicking elsewhere finishes the edit
+    /// and calls [`Self::on_tab_renamed`]. Escape cancels it.
+    fn can_rename_tab(&self, _tiles: &Tiles<Pane>, _tile_id: TileId) -> bool {
+        false
+    }
+
+    /// The user has finished renaming a tab.
+    ///
+    /// The title shown is always taken from [`Self::tab_title_for_tile`],
+    /// so you need to store the new title yourself, e.g. in the pane.
+    ///
+    /// Return `false` to reject the new title.
+    /// If the user pressed Enter, a rejected title stays open for editing.
+    fn on_tab_renamed(
+        &mut self,
+        _tiles: &mut Tiles<Pane>,
+        _tile_id: TileId,
+        _new_title: &str,
+    ) -> bool {
+        false
+    }
+
+    /// Should the tab of the given tile have a context menu with a "Duplicate" entry?
+    ///
+    /// Choosing it copies the tile with [`Self::duplicate_pane`], see [`crate::Tree::duplicate_with`].
+    /// The entry is only shown if [`Self::can_duplicate_pane`] is `true` for at least one pane in the tile.
+    fn can_duplicate_tab(&self, _tiles: &Tiles<Pane>, _tile_id: TileId) -> bool {
+        false
+    }
+
+    /// The label of the entry added by [`Self::can_duplicate_tab`].
+    fn duplicate_tab_label(&self) -> WidgetText {
+        "Duplicate".into()
+    }
+
+    /// Can this pane be copied with [`Self::duplicate_pane`]? Others are left out of duplicated tabs.
+    fn can_duplicate_pane(&self, _pane: &Pane) -> bool {
+        false
+    }
+
+    /// Make a copy of a pane, when a tab is duplicated from its context menu.
+    ///
+    /// Only called if [`Self::can_duplicate_pane`] returned `true`.
+    /// Return `None` to leave the pane out of the copy after all.
+    fn duplicate_pane(&mut self, _pane: &Pane) -> Option<Pane> {
+        None
+    }
+
+    /// Called after a tab was duplicated from its context menu.
+    fn on_tab_duplicated(&mut self, _tiles: &mut Tiles<Pane>, _original: TileId, _copy: TileId) {}
+
+    /// Return `false` if the given tile should not be draggable.
+    ///
+    /// The root tile and locked tiles (see [`Self::is_locked`]) can never be dragged.
+    fn can_drag(&self, _tiles: &Tiles<Pane>, _tile_id: TileId) -> bool {
+        true
+    }
+
+    /// Return `false` if the dragged tile may not be dropped at the given insertion point.
+    ///
+    /// Disallowed drop zones are neither suggested nor previewed.
+    fn can_drop(
+        &self,
+        _tiles: &Tiles<Pane>,
+        _dragged_tile_id: TileId,
+        _insertion_point: &InsertionPoint,
+    ) -> bool {
+        true
+    }
+
+    /// Return `false` if the external payload may not be dropped at the given insertion point.
+    ///
+    /// See [`DragPayload`].
+    fn can_drop_payload(
+        &self,
+        _tiles: &Tiles<Pane>,
+        _payload: &DragPayload,
+        _insertion_point: &InsertionPoint,
+    ) -> bool {
+        true
+    }
+
+    /// Called when an external [`DragPayload`] is dropped onto the tree.
+    ///
+    /// Return a pane to insert it where it was dropped, or `None` to ignore the drop.
+    fn create_pane_from_drop(&mut self, _payload: &DragPayload) -> Option<Pane> {
+        None
+    }
+
+    /// Return `true` to show drop zones for files dragged in from outside the application.
+    ///
+    /// Defaults to `false`, so by default hovering files are ignored.
+    fn can_drop_files(&self, _tiles: &Tiles<Pane>, _insertion_point: &InsertionPoint) -> bool {
+        false
+    }
+
+    /// Called when files are dropped onto the tree at an insertion point
+    /// allowed by [`Self::can_drop_files`].
+    ///
+    /// The returned panes are inserted next to each other, starting at the insertion point.
+    fn create_panes_from_files(
+        &mut self,
+        _files: &[egui::DroppedFile],
+        _insertion_point: &InsertionPoint,
+    ) -> Vec<Pane> {
+        vec![]
+    }
+
+    /// A locked tile can not be dragged, and nothing can be dropped into it.
+    fn is_locked(&self, _tiles: &Tiles<Pane>, _tile_id: TileId) -> bool {
+        false
+    }
+
+    /// Called when a pane is shown, after having been hidden (or not in the tree) the frame before.
+    ///
+    /// Panes in inactive tabs are hidden. Use this to e.g. resume expensive data subscriptions.
+    ///
+    /// These hooks compare consecutive calls to [`crate::Tree::ui`], so nothing is reported while a whole tree
+    /// is not shown at all; use [`crate::Tree::is_visible`] to tell.
+    fn on_pane_shown(&mut self, _tile_id: TileId, _pane: &mut Pane) {}
+
+    /// Called when a pane that was shown the frame before is now hidden, e.g. because another tab was selected.
+    ///
+    /// Not called for panes that were removed from the tree.
+    fn on_pane_hidden(&mut self, _tile_id: TileId, _pane: &mut Pane) {}
+
+    /// Show an empty placeholder, see [`crate::Tabs::placeholder`].
+    fn placeholder_ui(&mut self, ui: &mut Ui, _tile_id: TileId, label: &str) {
+        let rect = ui.max_rect().shrink(4.0);
+        let visuals = ui.visuals();
+        ui.painter()
+            .rect_stroke(rect, 4.0, visuals.widgets.noninteractive.bg_stroke);
+        ui.painter().text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            label,
+            TextStyle::Body.resolve(ui.style()),
+            visuals.weak_text_color(),
+        );
+    }
+
     /// Return `false` if a given pane should be removed from its parent.
+    ///
+    /// Asked by [`crate::Tree::gc`], which [`crate::Tree::ui`] runs every frame.
     fn retain_pane(&mut self, _pane: &Pane) -> bool {
         true
     }
@@ -133,6 +283,66 @@ pub trait Behavior<Pane> {
         // }
     }
 
+    /// Should we show a title bar above the given pane?
+    ///
+    /// This is only asked for panes that are not in a [`crate::Tabs`] container,
+    /// i.e. panes directly inside a [`crate::Linear`] or [`crate::Grid`], or at the root.
+    /// The title bar acts as a drag handle for the pane.
+    fn show_pane_title_bar(&self, _tile_id: TileId, _pane: &Pane) -> bool {
+        false
+    }
+
+    /// The height of the title bar shown above panes, if [`Self::show_pane_title_bar`].
+    fn pane_title_bar_height(&self, style: &egui::Style) -> f32 {
+        self.tab_bar_height(style)
+    }
+
+    /// Show the title bar of a pane, if [`Self::show_pane_title_bar`].
+    ///
+    /// The default implementation shows the title from [`Self::tab_title_for_pane`],
+    /// and calls [`Self::pane_title_bar_right_ui`] for any buttons.
+    ///
+    /// Return a response that senses drags if you want the title bar to act as a drag handle.
+    fn pane_title_bar_ui(&mut self, ui: &mut Ui, tile_id: TileId, pane: &mut Pane) -> Response {
+        let rect = ui.max_rect();
+        let response = ui
+            .interact(rect, ui.id().with("drag"), Sense::click_and_drag())
+            .on_hover_cursor(egui::CursorIcon::Grab);
+
+        if ui.is_rect_visible(rect) {
+            ui.painter()
+                .rect_filled(rect, 0.0, self.tab_bar_color(ui.visuals()));
+            ui.painter().hline(
+                rect.x_range(),
+                rect.bottom(),
+                self.tab_bar_hline_stroke(ui.visuals()),
+            );
+        }
+
+        let x_margin = self.tab_title_spacing(ui.visuals());
+        let title =
+            self.tab_title_for_pane(pane)
+                .color(self.tab_text_color(ui.visuals(), tile_id, true));
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            self.pane_title_bar_right_ui(ui, tile_id, pane);
+
+            ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                ui.add_space(x_margin);
+                ui.add(egui::Label::new(title).wrap(false));
+            });
+        });
+
+        response
+    }
+
+    /// Adds some UI to the right side of a pane title bar.
+    ///
+    /// You can use this to, for instance, add close or maximize buttons.
+    ///
+    /// The widgets will be added right-to-left.
+    fn pane_title_bar_right_ui(&mut self, _ui: &mut Ui, _tile_id: TileId, _pane: &mut Pane) {}
+
     /// The height of the bar holding tab titles.
     fn tab_bar_height(&self, _style: &egui::Style) -> f32 {
         24.0
@@ -144,22 +354,47 @@ pub trait Behavior<Pane> {
         1.0
     }
 
+    /// How many seconds to animate tiles into their new place
+    /// after the structure of the tree changes, e.g. after a drop.
+    ///
+    /// Zero (the default) means no animation.
+    fn layout_animation_time(&self) -> f32 {
+        0.0
+    }
+
     /// No child should shrink below this width nor height.
     fn min_size(&self) -> f32 {
         32.0
     }
 
-    /// Show we preview panes that are being dragged,
+    /// Should we preview panes that are being dragged,
     /// i.e. show their ui in the region where they will end up?
     fn preview_dragged_panes(&self) -> bool {
         false
     }
 
+    /// Should we preview containers that are being dragged,
+    /// i.e. lay out and show their whole subtree in the region where they will end up?
+    fn preview_dragged_containers(&self) -> bool {
+        false
+    }
+
+    /// If set, show a thumbnail of the dragged tile of this size under the mouse cursor,
+    /// below its title.
+    fn drag_thumbnail_size(&self, _style: &egui::Style) -> Option<Vec2> {
+        None
+    }
+
     /// Cover the tile that is being dragged with this color.
     fn dragged_overlay_color(&self, visuals: &Visuals) -> Color32 {
         visuals.panel_fill.gamma_multiply(0.5)
     }
 
+    /// When should [`crate::Tree::ui`] simplify the tree?
+    fn simplification_mode(&self) -> SimplificationMode {
+        SimplificationMode::default()
+    }
+
     /// What are the rules for simplifying the tree?
     fn simplification_options(&self) -> SimplificationOptions {
         SimplificationOptions::default()
@@ -246,6 +481,53 @@ pub trait Behavior<Pane> {
         );
     }
 
+    /// How to decide where a dragged tile will be dropped.
+    fn drop_target_mode(&self) -> DropTargetMode {
+        DropTargetMode::Nearest
+    }
+
+    /// The width and height of each button in the docking compass.
+    ///
+    /// Only used with [`DropTargetMode::Compass`].
+    fn compass_button_size(&self, _style: &egui::Style) -> f32 {
+        32.0
+    }
+
+    /// Paint one button of the docking compass.
+    ///
+    /// Only used with [`DropTargetMode::Compass`].
+    fn paint_compass_button(
+        &self,
+        visuals: &Visuals,
+        painter: &egui::Painter,
+        rect: Rect,
+        button: CompassButton,
+        hovered: bool,
+    ) {
+        let (fill, stroke) = if hovered {
+            (visuals.selection.bg_fill, visuals.selection.stroke)
+        } else {
+            (
+                visuals.window_fill,
+                visuals.widgets.noninteractive.bg_stroke,
+            )
+        };
+        painter.rect(rect, 4.0, fill, stroke);
+
+        // A small picture of where the tile will end up:
+        let icon_rect = rect.shrink(0.25 * rect.width());
+        let icon_color = visuals.selection.stroke.color;
+        let target_rect = match button {
+            CompassButton::Left => icon_rect.split_left_right_at_fraction(0.5).0,
+            CompassButton::Right => icon_rect.split_left_right_at_fraction(0.5).1,
+            CompassButton::Top => icon_rect.split_top_bottom_at_fraction(0.5).0,
+            CompassButton::Bottom => icon_rect.split_top_bottom_at_fraction(0.5).1,
+            CompassButton::Center => icon_rect,
+        };
+        painter.rect_stroke(icon_rect, 1.0, Stroke::new(1.0, icon_color));
+        painter.rect_filled(target_rect, 1.0, icon_color.gamma_multiply(0.5));
+    }
+
     /// How many columns should we use for a [`crate::Grid`] put into [`crate::GridLayout::Auto`]?
     ///
     /// The default heuristic tried to find a good column count that results in a per-tile aspect-ratio
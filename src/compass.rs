@@ -0,0 +1,123 @@
+use egui::{vec2, Pos2, Rect, Vec2};
+
+use super::{ContainerInsertion, InsertionPoint, TileId};
+
+/// How to decide where a dragged tile will be dropped.
+///
+/// See [`crate::Behavior::drop_target_mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DropTargetMode {
+    /// Pick the drop zone whose center is closest to the mouse.
+    #[default]
+    Nearest,
+
+    /// Show a docking compass over the hovered tile,
+    /// and only drop when the mouse is over one of its buttons.
+    ///
+    /// The root tile additionally gets buttons along its outer edges.
+    Compass,
+}
+
+/// One of the buttons in the docking compass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompassButton {
+    Left,
+    Right,
+    Top,
+    Bottom,
+
+    /// Add as a tab.
+    Center,
+}
+
+/// A button in the docking compass, and what it does.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct CompassTarget {
+    pub button: CompassButton,
+    pub button_rect: Rect,
+    pub insertion: InsertionPoint,
+    pub preview_rect: Rect,
+}
+
+/// The five buttons shown in the middle of the hovered tile.
+pub(super) fn tile_compass(
+    tile_id: TileId,
+    rect: Rect,
+    button_size: f32,
+    tab_bar_height: f32,
+) -> Vec<CompassTarget> {
+    let spacing = button_size + 0.25 * button_size;
+    let center = rect.center();
+    let button_rect =
+        |offset: Vec2| Rect::from_center_size(center + offset, Vec2::splat(button_size));
+
+    let (left, right) = rect.split_left_right_at_fraction(0.5);
+    let (top, bottom) = rect.split_top_bottom_at_fraction(0.5);
+
+    vec![
+        CompassTarget {
+            button: CompassButton::Left,
+            button_rect: button_rect(vec2(-spacing, 0.0)),
+            insertion: InsertionPoint::new(tile_id, ContainerInsertion::Horizontal(0)),
+            preview_rect: left,
+        },
+        CompassTarget {
+            button: CompassButton::Right,
+            button_rect: button_rect(vec2(spacing, 0.0)),
+            insertion: InsertionPoint::new(tile_id, ContainerInsertion::Horizontal(usize::MAX)),
+            preview_rect: right,
+        },
+        CompassTarget {
+            button: CompassButton::Top,
+            button_rect: button_rect(vec2(0.0, -spacing)),
+            insertion: InsertionPoint::new(tile_id, ContainerInsertion::Vertical(0)),
+            preview_rect: top,
+        },
+        CompassTarget {
+            button: CompassButton::Bottom,
+            button_rect: button_rect(vec2(0.0, spacing)),
+            insertion: InsertionPoint::new(tile_id, ContainerInsertion::Vertical(usize::MAX)),
+            preview_rect: bottom,
+        },
+        CompassTarget {
+            button: CompassButton::Center,
+            button_rect: button_rect(Vec2::ZERO),
+            insertion: InsertionPoint::new(tile_id, ContainerInsertion::Tabs(usize::MAX)),
+            preview_rect: rect.split_top_bottom_at_y(rect.top() + tab_bar_height).1,
+        },
+    ]
+}
+
+/// Buttons along the outer edges of the root, for docking next to everything else.
+pub(super) fn root_edges(root_id: TileId, rect: Rect, button_size: f32) -> Vec<CompassTarget> {
+    let margin = 0.25 * button_size;
+    let inset = margin + 0.5 * button_size;
+    let button_rect = |center: Pos2| Rect::from_center_size(center, Vec2::splat(button_size));
+
+    vec![
+        CompassTarget {
+            button: CompassButton::Left,
+            button_rect: button_rect(Pos2::new(rect.left() + inset, rect.center().y)),
+            insertion: InsertionPoint::new(root_id, ContainerInsertion::Horizontal(0)),
+            preview_rect: rect.split_left_right_at_fraction(0.25).0,
+        },
+        CompassTarget {
+            button: CompassButton::Right,
+            button_rect: button_rect(Pos2::new(rect.right() - inset, rect.center().y)),
+            insertion: InsertionPoint::new(root_id, ContainerInsertion::Horizontal(usize::MAX)),
+            preview_rect: rect.split_left_right_at_fraction(0.75).1,
+        },
+        CompassTarget {
+            button: CompassButton::Top,
+            button_rect: button_rect(Pos2::new(rect.center().x, rect.top() + inset)),
+            insertion: InsertionPoint::new(root_id, ContainerInsertion::Vertical(0)),
+            preview_rect: rect.split_top_bottom_at_fraction(0.25).0,
+        },
+        CompassTarget {
+            button: CompassButton::Bottom,
+            button_rect: button_rect(Pos2::new(rect.center().x, rect.bottom() - inset)),
+            insertion: InsertionPoint::new(root_id, ContainerInsertion::Vertical(usize::MAX)),
+            preview_rect: rect.split_top_bottom_at_fraction(0.75).1,
+        },
+    ]
+}
@@ -5,7 +5,7 @@ use itertools::Itertools as _;
 
 use crate::{
     Behavior, ContainerInsertion, DropContext, InsertionPoint, ResizeState, SimplifyAction, TileId,
-    Tiles, Tree,
+    Tiles, TilingStrategy, Tree,
 };
 
 /// A location in a grid (row and column).
@@ -77,6 +77,14 @@ pub struct Grid {
     /// Share of the available height assigned to each row.
     pub row_shares: Vec<f32>,
 
+    /// Never remove this container when simplifying, see [`crate::Tabs::keep_if_empty`].
+    #[serde(default)]
+    pub keep_if_empty: bool,
+
+    /// Keep the panes in this container arranged with this strategy, see [`crate::Tree::set_auto_tiling`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_tiling: Option<TilingStrategy>,
+
     /// ui point x ranges for each column, recomputed during layout
     #[serde(skip)]
     col_ranges: Vec<Rangef>,
@@ -205,7 +213,7 @@ impl Grid {
         tile_id: TileId,
     ) {
         for &child in &self.children {
-            tree.tile_ui(behavior, drop_context, ui, child);
+            tree.tile_ui(behavior, drop_context, ui, child, false);
             crate::cover_tile_if_dragged(tree, behavior, ui, child);
         }
 
@@ -313,7 +321,10 @@ impl Grid {
 
     pub(super) fn simplify_children(&mut self, mut simplify: impl FnMut(TileId) -> SimplifyAction) {
         self.children.retain_mut(|child| match simplify(*child) {
-            SimplifyAction::Remove => false,
+            SimplifyAction::Remove => {
+                self.locations.remove(child);
+                false
+            }
             SimplifyAction::Keep => true,
             SimplifyAction::Replace(new) => {
                 if let Some(loc) = self.locations.remove(child) {
@@ -3,7 +3,7 @@ use itertools::Itertools as _;
 
 use crate::{
     is_being_dragged, Behavior, ContainerInsertion, DropContext, InsertionPoint, ResizeState,
-    SimplifyAction, TileId, Tiles, Tree,
+    SimplifyAction, TileId, Tiles, TilingStrategy, Tree,
 };
 
 // ----------------------------------------------------------------------------
@@ -95,6 +95,14 @@ pub struct Linear {
     pub children: Vec<TileId>,
     pub dir: LinearDir,
     pub shares: Shares,
+
+    /// Never remove this container when simplifying, see [`crate::Tabs::keep_if_empty`].
+    #[serde(default)]
+    pub keep_if_empty: bool,
+
+    /// Keep the panes in this container arranged with this strategy, see [`crate::Tree::set_auto_tiling`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_tiling: Option<TilingStrategy>,
 }
 
 impl Linear {
@@ -213,7 +221,7 @@ impl Linear {
         parent_id: TileId,
     ) {
         for &child in &self.children {
-            tree.tile_ui(behavior, drop_context, ui, child);
+            tree.tile_ui(behavior, drop_context, ui, child, false);
             crate::cover_tile_if_dragged(tree, behavior, ui, child);
         }
 
@@ -275,7 +283,7 @@ impl Linear {
         parent_id: TileId,
     ) {
         for &child in &self.children {
-            tree.tile_ui(behavior, drop_context, ui, child);
+            tree.tile_ui(behavior, drop_context, ui, child, false);
             crate::cover_tile_if_dragged(tree, behavior, ui, child);
         }
 
@@ -330,7 +338,10 @@ impl Linear {
 
     pub(super) fn simplify_children(&mut self, mut simplify: impl FnMut(TileId) -> SimplifyAction) {
         self.children.retain_mut(|child| match simplify(*child) {
-            SimplifyAction::Remove => false,
+            SimplifyAction::Remove => {
+                self.shares.retain(|id| id != *child);
+                false
+            }
             SimplifyAction::Keep => true,
             SimplifyAction::Replace(new) => {
                 self.shares.replace_with(*child, new);
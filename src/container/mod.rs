@@ -1,6 +1,6 @@
 use egui::Rect;
 
-use crate::Tree;
+use crate::{TilingStrategy, Tree};
 
 use super::{Behavior, DropContext, SimplifyAction, TileId, Tiles};
 
@@ -17,7 +17,9 @@ pub use tabs::Tabs;
 /// The layout type of a [`Container`].
 ///
 /// This is used to describe a [`Container`], and to change it to a different layout type.
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
 pub enum ContainerKind {
     /// Each child in an individual tab.
     #[default]
@@ -129,11 +131,55 @@ impl Container {
         }
     }
 
+    /// Is this an empty placeholder, see [`Tabs::placeholder`].
+    pub fn is_placeholder(&self) -> bool {
+        matches!(self, Self::Tabs(tabs) if tabs.is_placeholder())
+    }
+
+    /// See [`Tabs::keep_if_empty`].
+    pub fn keep_if_empty(&self) -> bool {
+        match self {
+            Self::Tabs(tabs) => tabs.keep_if_empty,
+            Self::Linear(linear) => linear.keep_if_empty,
+            Self::Grid(grid) => grid.keep_if_empty,
+        }
+    }
+
+    /// See [`Tabs::keep_if_empty`].
+    pub fn set_keep_if_empty(&mut self, keep_if_empty: bool) {
+        match self {
+            Self::Tabs(tabs) => tabs.keep_if_empty = keep_if_empty,
+            Self::Linear(linear) => linear.keep_if_empty = keep_if_empty,
+            Self::Grid(grid) => grid.keep_if_empty = keep_if_empty,
+        }
+    }
+
+    /// See [`crate::Tree::set_auto_tiling`].
+    ///
+    /// Always `None` for [`Tabs`], which [`crate::Tree::retile`] never produces.
+    pub fn auto_tiling(&self) -> Option<TilingStrategy> {
+        match self {
+            Self::Tabs(_) => None,
+            Self::Linear(linear) => linear.auto_tiling,
+            Self::Grid(grid) => grid.auto_tiling,
+        }
+    }
+
+    pub(super) fn set_auto_tiling(&mut self, auto_tiling: Option<TilingStrategy>) {
+        match self {
+            Self::Tabs(_) => {}
+            Self::Linear(linear) => linear.auto_tiling = auto_tiling,
+            Self::Grid(grid) => grid.auto_tiling = auto_tiling,
+        }
+    }
+
     pub fn set_kind(&mut self, kind: ContainerKind) {
         if kind == self.kind() {
             return;
         }
 
+        let keep_if_empty = self.keep_if_empty();
+        let auto_tiling = self.auto_tiling();
         *self = match kind {
             ContainerKind::Tabs => Self::Tabs(Tabs::new(self.children().to_vec())),
             ContainerKind::Horizontal => {
@@ -144,6 +190,8 @@ impl Container {
             }
             ContainerKind::Grid => Self::Grid(Grid::new(self.children().to_vec())),
         };
+        self.set_keep_if_empty(keep_if_empty);
+        self.set_auto_tiling(auto_tiling);
     }
 
     pub(super) fn retain(&mut self, mut retain: impl FnMut(TileId) -> bool) {
@@ -1,10 +1,16 @@
-use egui::{scroll_area::ScrollBarVisibility, vec2, Rect, Vec2};
+use egui::{
+    scroll_area::ScrollBarVisibility,
+    text::{CCursor, CCursorRange},
+    vec2, NumExt as _, Rect, TextStyle, Vec2,
+};
 
 use crate::{
     is_being_dragged, Behavior, ContainerInsertion, DropContext, InsertionPoint, SimplifyAction,
     TileId, Tiles, Tree,
 };
 
+use crate::tree::{can_duplicate_tab, request_duplicate};
+
 /// A container with tabs. Only one tab is open (active) at a time.
 #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct Tabs {
@@ -13,6 +19,20 @@ pub struct Tabs {
 
     /// The currently open tab.
     pub active: Option<TileId>,
+
+    /// Never remove this container when simplifying the tree, even if it is empty or has a single child.
+    ///
+    /// Use this for placeholders that should stay around as drop targets for future panes.
+    #[serde(default)]
+    pub keep_if_empty: bool,
+
+    /// If set, this is an empty region waiting for something to be dropped into it,
+    /// e.g. "Drop a chart here".
+    ///
+    /// While empty it is shown with [`crate::Behavior::placeholder_ui`] and never simplified away.
+    /// When something is dropped into it, that tile takes its place.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
 }
 
 #[derive(Default, Clone)]
@@ -26,10 +46,121 @@ struct ScrollState {
     pub prev_frame_right: bool,
 }
 
+/// A tab whose title is currently being edited by the user.
+#[derive(Clone)]
+struct TabRename {
+    tile_id: TileId,
+
+    /// The title as typed so far.
+    text: String,
+
+    /// Focus the text edit (and select all of it) next time it is shown.
+    request_focus: bool,
+}
+
+impl TabRename {
+    fn new(tile_id: TileId, text: String) -> Self {
+        Self {
+            tile_id,
+            text,
+            request_focus: true,
+        }
+    }
+
+    /// Show a text edit in place of the tab button.
+    ///
+    /// Returns the response, and whether or not we are still editing.
+    fn ui<Pane>(
+        &mut self,
+        behavior: &mut dyn Behavior<Pane>,
+        tiles: &mut Tiles<Pane>,
+        ui: &mut egui::Ui,
+        active: bool,
+    ) -> (egui::Response, bool) {
+        let font_id = TextStyle::Button.resolve(ui.style());
+        let text_color = behavior.tab_text_color(ui.visuals(), self.tile_id, active);
+
+        // Measure the text every frame, so that the tab grows and shrinks while typing:
+        let text_width = ui
+            .fonts(|fonts| fonts.layout_no_wrap(self.text.clone(), font_id.clone(), text_color))
+            .size()
+            .x
+            .at_least(ui.spacing().interact_size.x);
+
+        let x_margin = behavior.tab_title_spacing(ui.visuals());
+        let (_, rect) = ui.allocate_space(vec2(text_width + 2.0 * x_margin, ui.available_height()));
+
+        if ui.is_rect_visible(rect) {
+            let bg_color = behavior.tab_bg_color(ui.visuals(), self.tile_id, true);
+            let stroke = behavior.tab_outline_stroke(ui.visuals(), self.tile_id, true);
+            ui.painter().rect(rect.shrink(0.5), 0.0, bg_color, stroke);
+        }
+
+        let mut edit_ui = ui.child_ui(
+            rect.shrink2(vec2(x_margin, 0.0)),
+            egui::Layout::left_to_right(egui::Align::Center),
+        );
+        let mut output = egui::TextEdit::singleline(&mut self.text)
+            .id(ui.id().with((self.tile_id, "rename")))
+            .font(font_id)
+            .text_color(text_color)
+            .frame(false)
+            .margin(Vec2::ZERO)
+            .desired_width(text_width)
+            .show(&mut edit_ui);
+
+        if self.request_focus {
+            self.request_focus = false;
+            output.response.request_focus();
+            output.state.set_ccursor_range(Some(CCursorRange::two(
+                CCursor::new(0),
+                CCursor::new(self.text.chars().count()),
+            )));
+            output.state.store(ui.ctx(), output.response.id);
+            return (output.response, true);
+        }
+
+        let still_editing = if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+            false // cancel
+        } else if output.response.lost_focus() {
+            let accepted = behavior.on_tab_renamed(tiles, self.tile_id, &self.text);
+            if !accepted && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                // Let the user keep editing:
+                output.response.request_focus();
+                true
+            } else {
+                false
+            }
+        } else {
+            true
+        };
+
+        (output.response, still_editing)
+    }
+}
+
 impl Tabs {
     pub fn new(children: Vec<TileId>) -> Self {
         let active = children.first().copied();
-        Self { children, active }
+        Self {
+            children,
+            active,
+            keep_if_empty: false,
+            placeholder: None,
+        }
+    }
+
+    /// An empty placeholder with the given label, see [`Self::placeholder`].
+    pub fn new_placeholder(label: impl Into<String>) -> Self {
+        Self {
+            placeholder: Some(label.into()),
+            ..Self::new(vec![])
+        }
+    }
+
+    /// Is this an empty placeholder, see [`Self::placeholder`].
+    pub fn is_placeholder(&self) -> bool {
+        self.placeholder.is_some() && self.children.is_empty()
     }
 
     pub fn add_child(&mut self, child: TileId) {
@@ -74,10 +205,16 @@ impl Tabs {
         rect: Rect,
         tile_id: TileId,
     ) {
+        if let (Some(label), true) = (&self.placeholder, self.children.is_empty()) {
+            let mut ui = ui.child_ui(rect, *ui.layout());
+            behavior.placeholder_ui(&mut ui, tile_id, label);
+            return;
+        }
+
         let next_active = self.tab_bar_ui(tree, behavior, ui, rect, drop_context, tile_id);
 
         if let Some(active) = self.active {
-            tree.tile_ui(behavior, drop_context, ui, active);
+            tree.tile_ui(behavior, drop_context, ui, active, true);
             crate::cover_tile_if_dragged(tree, behavior, ui, active);
         }
 
@@ -110,6 +247,11 @@ impl Tabs {
             }
         });
 
+        let rename_id = id.with("rename");
+        let mut rename: Option<TabRename> = ui
+            .data(|data| data.get_temp::<TabRename>(rename_id))
+            .filter(|rename| self.children.contains(&rename.tile_id)); // Closed or moved away
+
         let tab_bar_height = behavior.tab_bar_height(ui.style());
         let tab_bar_rect = rect.split_top_bottom_at_y(rect.top() + tab_bar_height).0;
         let mut ui = ui.child_ui(tab_bar_rect, *ui.layout());
@@ -231,22 +373,62 @@ impl Tabs {
                                 let selected = self.is_active(child_id);
                                 let id = child_id.id();
 
-                                let response = behavior.tab_ui(
-                                    &tree.tiles,
-                                    ui,
-                                    id,
-                                    child_id,
-                                    selected,
-                                    is_being_dragged,
-                                );
-                                let response = response.on_hover_cursor(egui::CursorIcon::Grab);
-                                if response.clicked() {
-                                    next_active = Some(child_id);
-                                    response.scroll_to_me(None)
-                                }
+                                let response = match rename.take() {
+                                    Some(mut state) if state.tile_id == child_id => {
+                                        let (response, still_editing) =
+                                            state.ui(behavior, &mut tree.tiles, ui, selected);
+                                        if still_editing {
+                                            rename = Some(state);
+                                        }
+                                        response
+                                    }
+                                    other => {
+                                        rename = other;
+
+                                        let response = behavior.tab_ui(
+                                            &tree.tiles,
+                                            ui,
+                                            id,
+                                            child_id,
+                                            selected,
+                                            is_being_dragged,
+                                        );
+                                        let mut response =
+                                            response.on_hover_cursor(egui::CursorIcon::Grab);
+                                        if can_duplicate_tab(&tree.tiles, behavior, child_id) {
+                                            let label = behavior.duplicate_tab_label();
+                                            response = response.context_menu(|ui| {
+                                                if ui.button(label).clicked() {
+                                                    request_duplicate(ui.ctx(), tree, child_id);
+                                                    ui.close_menu();
+                                                }
+                                            });
+                                        }
+                                        if response.clicked() {
+                                            next_active = Some(child_id);
+                                            response.scroll_to_me(None)
+                                        }
+
+                                        let rename_requested = response.double_clicked()
+                                            || ((response.hovered() || response.has_focus())
+                                                && ui.input(|i| i.key_pressed(egui::Key::F2)));
+                                        if rename_requested
+                                            && behavior.can_rename_tab(&tree.tiles, child_id)
+                                        {
+                                            let title = behavior
+                                                .tab_title_for_tile(&tree.tiles, child_id)
+                                                .text()
+                                                .to_owned();
+                                            rename = Some(TabRename::new(child_id, title));
+                                            next_active = Some(child_id);
+                                        }
+
+                                        response
+                                    }
+                                };
 
                                 if let Some(mouse_pos) = drop_context.mouse_pos {
-                                    if drop_context.dragged_tile_id.is_some()
+                                    if drop_context.is_dragging()
                                         && response.rect.contains(mouse_pos)
                                     {
                                         // Expand this tab - maybe the user wants to drop something into it!
@@ -285,6 +467,11 @@ impl Tabs {
                 .memory_mut(|m| m.data.insert_temp(id, scroll_state));
         });
 
+        ui.data_mut(|data| match rename {
+            Some(rename) => data.insert_temp(rename_id, rename),
+            None => data.remove::<TabRename>(rename_id),
+        });
+
         // -----------
         // Drop zones:
 
@@ -320,8 +507,12 @@ impl Tabs {
     }
 
     pub(super) fn simplify_children(&mut self, mut simplify: impl FnMut(TileId) -> SimplifyAction) {
+        let mut removed_active = false;
         self.children.retain_mut(|child| match simplify(*child) {
-            SimplifyAction::Remove => false,
+            SimplifyAction::Remove => {
+                removed_active |= self.active == Some(*child);
+                false
+            }
             SimplifyAction::Keep => true,
             SimplifyAction::Replace(new) => {
                 if self.active == Some(*child) {
@@ -331,5 +522,8 @@ impl Tabs {
                 true
             }
         });
+        if removed_active {
+            self.active = self.children.first().copied();
+        }
     }
 }
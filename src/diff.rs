@@ -0,0 +1,484 @@
+//! Computing the difference between two [`Tree`]s, and applying it as a patch.
+
+use super::{Container, GridLoc, Tile, TileId, Tree};
+
+/// One change in a [`TreeDiff`].
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TileChange<Pane> {
+    /// Add a tile, or replace it wholesale.
+    ///
+    /// Used for new tiles, for panes whose contents changed,
+    /// and for containers that changed in ways not covered by the other changes (e.g. their kind).
+    Insert { tile_id: TileId, tile: Tile<Pane> },
+
+    /// Remove a tile.
+    ///
+    /// Its parent is updated by a separate [`Self::SetChildren`].
+    Remove(TileId),
+
+    /// Set the children of a container, in order.
+    SetChildren {
+        container: TileId,
+        children: Vec<TileId>,
+    },
+
+    /// Set or clear the share of a child of a [`crate::Linear`].
+    SetShare {
+        container: TileId,
+        child: TileId,
+        share: Option<f32>,
+    },
+
+    /// Set [`crate::Tabs::active`].
+    SetActive {
+        container: TileId,
+        active: Option<TileId>,
+    },
+
+    /// Set or clear the location of a child of a [`crate::Grid`].
+    SetGridLocation {
+        container: TileId,
+        child: TileId,
+        location: Option<GridLoc>,
+    },
+
+    /// Set [`Tree::root`].
+    SetRoot(Option<TileId>),
+}
+
+/// The changes that turn one [`Tree`] into another, see [`Tree::diff`].
+///
+/// Tiles are matched up by [`TileId`], so this works best for trees that share a history,
+/// e.g. the same tree before and after the user rearranged it.
+///
+/// Applying the diff of `a` and `b` to `a` gives `b` again:
+///
+/// ```
+/// use egui_tiles::Tree;
+///
+/// let a = Tree::from_layout_string("h[a, v[b, c]]", |name| Some(name.to_owned())).unwrap();
+/// let b = Tree::from_layout_string("v[c, 2:h[b, a]]", |name| Some(name.to_owned())).unwrap();
+///
+/// let mut patched = a.clone();
+/// patched.apply(&a.diff(&b));
+/// assert_eq!(patched.to_layout_string(|pane| pane.clone()), "v[c, 2:h[b, a]]");
+/// ```
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TreeDiff<Pane> {
+    /// In the order they should be applied.
+    pub changes: Vec<TileChange<Pane>>,
+}
+
+impl<Pane> Default for TreeDiff<Pane> {
+    fn default() -> Self {
+        Self { changes: vec![] }
+    }
+}
+
+impl<Pane> TreeDiff<Pane> {
+    /// `true` if the trees were the same.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+}
+
+impl<Pane: Clone + PartialEq> Tree<Pane> {
+    /// The changes that turn `self` into `other`.
+    ///
+    /// Containers that only had their children, shares, active tab or grid locations changed
+    /// are described by just those changes; other changed tiles are replaced as a whole.
+    ///
+    /// Unreachable tiles are diffed too, so that [`Self::apply`] reproduces `other` exactly.
+    pub fn diff(&self, other: &Self) -> TreeDiff<Pane> {
+        let mut inserts = vec![];
+        let mut edits = vec![];
+        let mut removes = vec![];
+
+        let mut ids: Vec<TileId> = self
+            .tiles
+            .tiles
+            .keys()
+            .chain(other.tiles.tiles.keys())
+            .copied()
+            .collect();
+        ids.sort_by_key(TileId::as_u64);
+        ids.dedup();
+
+        for tile_id in ids {
+            match (self.tiles.get(tile_id), other.tiles.get(tile_id)) {
+                (Some(_), None) => removes.push(TileChange::Remove(tile_id)),
+                (None, Some(tile)) => inserts.push(TileChange::Insert {
+                    tile_id,
+                    tile: tile.clone(),
+                }),
+                (Some(Tile::Container(old)), Some(Tile::Container(new)))
+                    if same_settings(old, new) =>
+                {
+                    diff_container(tile_id, old, new, &mut edits);
+                }
+                (Some(old), Some(new)) => {
+                    let same = match (old, new) {
+                        (Tile::Pane(old), Tile::Pane(new)) => old == new,
+                        _ => false,
+                    };
+                    if !same {
+                        inserts.push(TileChange::Insert {
+                            tile_id,
+                            tile: new.clone(),
+                        });
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+
+        let mut changes = inserts;
+        changes.extend(edits);
+        changes.extend(removes);
+        if self.root != other.root {
+            changes.push(TileChange::SetRoot(other.root));
+        }
+        TreeDiff { changes }
+    }
+
+    /// Apply a diff computed with [`Self::diff`].
+    ///
+    /// Changes to tiles that don't exist (or have the wrong kind) are skipped,
+    /// so a diff can be applied to a tree other than the one it was computed from,
+    /// as long as the two are similar enough.
+    pub fn apply(&mut self, diff: &TreeDiff<Pane>) {
+        for change in &diff.changes {
+            match change {
+                TileChange::Insert { tile_id, tile } => {
+                    self.tiles.key_index.invalidate();
+                    self.tiles.tiles.insert(*tile_id, tile.clone());
+                }
+                TileChange::Remove(tile_id) => {
+                    self.tiles.tiles.remove(tile_id);
+                    self.tiles.rects.remove(tile_id);
+                }
+                TileChange::SetChildren {
+                    container,
+                    children,
+                } => match self.tiles.get_mut(*container) {
+                    Some(Tile::Container(Container::Tabs(tabs))) => {
+                        tabs.children = children.clone();
+                    }
+                    Some(Tile::Container(Container::Linear(linear))) => {
+                        linear.children = children.clone();
+                    }
+                    Some(Tile::Container(Container::Grid(grid))) => {
+                        grid.children = children.clone();
+                    }
+                    _ => log::warn!("Can't set the children of {container:?}"),
+                },
+                TileChange::SetShare {
+                    container,
+                    child,
+                    share,
+                } => {
+                    if let Some(Tile::Container(Container::Linear(linear))) =
+                        self.tiles.get_mut(*container)
+                    {
+                        if let Some(share) = share {
+                            linear.shares[*child] = *share;
+                        } else {
+                            linear.shares.retain(|it| it != *child);
+                        }
+                    } else {
+                        log::warn!("Can't set a share of {container:?}: not a linear container");
+                    }
+                }
+                TileChange::SetActive { container, active } => {
+                    if let Some(Tile::Container(Container::Tabs(tabs))) =
+                        self.tiles.get_mut(*container)
+                    {
+                        tabs.active = *active;
+                    } else {
+                        log::warn!("Can't set the active tab of {container:?}: not tabs");
+                    }
+                }
+                TileChange::SetGridLocation {
+                    container,
+                    child,
+                    location,
+                } => {
+                    if let Some(Tile::Container(Container::Grid(grid))) =
+                        self.tiles.get_mut(*container)
+                    {
+                        if let Some(location) = location {
+                            grid.locations.insert(*child, *location);
+                        } else {
+                            grid.locations.remove(child);
+                        }
+                    } else {
+                        log::warn!("Can't set a grid location in {container:?}: not a grid");
+                    }
+                }
+                TileChange::SetRoot(root) => {
+                    self.root = *root;
+                }
+            }
+        }
+    }
+}
+
+/// Everything but the children, shares, active tab and grid locations is the same.
+fn same_settings(old: &Container, new: &Container) -> bool {
+    match (old, new) {
+        (Container::Tabs(old), Container::Tabs(new)) => {
+            old.keep_if_empty == new.keep_if_empty && old.placeholder == new.placeholder
+        }
+        (Container::Linear(old), Container::Linear(new)) => {
+            old.dir == new.dir
+                && old.keep_if_empty == new.keep_if_empty
+                && old.auto_tiling == new.auto_tiling
+        }
+        (Container::Grid(old), Container::Grid(new)) => {
+            old.layout == new.layout
+                && old.col_shares == new.col_shares
+                && old.row_shares == new.row_shares
+                && old.keep_if_empty == new.keep_if_empty
+                && old.auto_tiling == new.auto_tiling
+        }
+        _ => false,
+    }
+}
+
+fn diff_container<Pane>(
+    container: TileId,
+    old: &Container,
+    new: &Container,
+    changes: &mut Vec<TileChange<Pane>>,
+) {
+    if old.children() != new.children() {
+        changes.push(TileChange::SetChildren {
+            container,
+            children: new.children().to_vec(),
+        });
+    }
+
+    match (old, new) {
+        (Container::Tabs(old), Container::Tabs(new)) if old.active != new.active => {
+            changes.push(TileChange::SetActive {
+                container,
+                active: new.active,
+            });
+        }
+        (Container::Linear(old), Container::Linear(new)) => {
+            // Also the shares of children that are gone, so they don't linger after `apply`:
+            let old_shares: nohash_hasher::IntMap<TileId, f32> =
+                old.shares.into_iter().map(|(&c, &s)| (c, s)).collect();
+            let new_shares: nohash_hasher::IntMap<TileId, f32> =
+                new.shares.into_iter().map(|(&c, &s)| (c, s)).collect();
+            let mut children: Vec<TileId> = old_shares
+                .keys()
+                .chain(new_shares.keys())
+                .copied()
+                .collect();
+            children.sort_by_key(TileId::as_u64);
+            children.dedup();
+            for child in children {
+                let share = new_shares.get(&child).copied();
+                if old_shares.get(&child).copied() != share {
+                    changes.push(TileChange::SetShare {
+                        container,
+                        child,
+                        share,
+                    });
+                }
+            }
+        }
+        (Container::Grid(old), Container::Grid(new)) => {
+            let mut children: Vec<TileId> = old
+                .locations
+                .keys()
+                .chain(new.locations.keys())
+                .copied()
+                .collect();
+            children.sort_by_key(TileId::as_u64);
+            children.dedup();
+            for child in children {
+                let location = new.locations.get(&child).copied();
+                if old.locations.get(&child).copied() != location {
+                    changes.push(TileChange::SetGridLocation {
+                        container,
+                        child,
+                        location,
+                    });
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ContainerKind, SimplifyAction};
+
+    fn remove_child(child: TileId, removed: TileId) -> SimplifyAction {
+        if child == removed {
+            SimplifyAction::Remove
+        } else {
+            SimplifyAction::Keep
+        }
+    }
+
+    fn tree(layout: &str) -> Tree<String> {
+        Tree::from_layout_string(layout, |name| Some(name.to_owned())).expect("valid layout")
+    }
+
+    fn find(tree: &Tree<String>, name: &str) -> TileId {
+        tree.tiles
+            .tiles
+            .iter()
+            .find_map(|(&tile_id, tile)| match tile {
+                Tile::Pane(pane) if pane == name => Some(tile_id),
+                _ => None,
+            })
+            .expect("the pane is in the tree")
+    }
+
+    fn container(tree: &mut Tree<String>, tile_id: TileId) -> &mut Container {
+        match tree.tiles.get_mut(tile_id) {
+            Some(Tile::Container(container)) => container,
+            _ => panic!("{tile_id:?} should be a container"),
+        }
+    }
+
+    /// Applying the diff from `a` to `b` to a copy of `a` gives `b`.
+    fn assert_diff_applies(a: &Tree<String>, b: &Tree<String>) {
+        let diff = a.diff(b);
+        assert!(!diff.is_empty());
+
+        let mut patched = a.clone();
+        patched.apply(&diff);
+        assert_eq!(patched.to_serialized(), b.to_serialized());
+        assert!(patched.diff(b).is_empty());
+
+        // Diffs can be saved and loaded:
+        let json = serde_json::to_string(&diff).expect("diffs can be serialized");
+        let loaded: TreeDiff<String> =
+            serde_json::from_str(&json).expect("diffs can be deserialized");
+        assert_eq!(loaded, diff);
+    }
+
+    #[test]
+    fn no_changes_no_diff() {
+        let a = tree("h[a, v[b, tabs[c, *d]], grid[e, f]]");
+        assert!(a.diff(&a).is_empty());
+        assert!(a.diff(&a.clone()).is_empty());
+    }
+
+    #[test]
+    fn added_pane() {
+        let a = tree("h[a, v[b, c]]");
+        let mut b = a.clone();
+        let d = b.tiles.insert_pane("d".to_owned());
+        let parent = b.tiles.parent_of(find(&b, "b")).expect("b has a parent");
+        container(&mut b, parent).add_child(d);
+        assert_diff_applies(&a, &b);
+    }
+
+    #[test]
+    fn removed_pane() {
+        let a = tree("h[a, v[b, 3:c]]");
+        let mut b = a.clone();
+        let c = find(&b, "c");
+        let parent = b.tiles.parent_of(c).expect("c has a parent");
+        container(&mut b, parent).simplify_children(|child| remove_child(child, c));
+        b.tiles.tiles.remove(&c);
+        assert_diff_applies(&a, &b);
+    }
+
+    #[test]
+    fn moved_pane() {
+        let a = tree("h[a, v[b, c]]");
+        let mut b = a.clone();
+        let root = b.root().expect("the tree has a root");
+        let (a_id, c) = (find(&b, "a"), find(&b, "c"));
+        let parent = b.tiles.parent_of(c).expect("c has a parent");
+        container(&mut b, root).simplify_children(|child| remove_child(child, a_id));
+        container(&mut b, parent).add_child(a_id);
+        assert_diff_applies(&a, &b);
+    }
+
+    #[test]
+    fn edited_pane() {
+        let a = tree("h[a, b]");
+        let mut b = a.clone();
+        let a_id = find(&b, "a");
+        if let Some(Tile::Pane(pane)) = b.tiles.get_mut(a_id) {
+            pane.push('!');
+        }
+        assert_diff_applies(&a, &b);
+    }
+
+    #[test]
+    fn changed_share() {
+        let a = tree("h[a, 2:b]");
+        let mut b = a.clone();
+        let root = b.root().expect("the tree has a root");
+        let b_id = find(&b, "b");
+        if let Container::Linear(linear) = container(&mut b, root) {
+            linear.shares[b_id] = 3.0;
+        }
+        assert_diff_applies(&a, &b);
+    }
+
+    #[test]
+    fn changed_active_tab() {
+        let a = tree("tabs[a, b, c]");
+        let mut b = a.clone();
+        let root = b.root().expect("the tree has a root");
+        let c = find(&b, "c");
+        if let Container::Tabs(tabs) = container(&mut b, root) {
+            tabs.set_active(c);
+        }
+        assert_diff_applies(&a, &b);
+    }
+
+    #[test]
+    fn changed_kind_and_root() {
+        let a = tree("h[a, v[b, c]]");
+        let mut b = a.clone();
+        let root = b.root().expect("the tree has a root");
+        container(&mut b, root).set_kind(ContainerKind::Grid);
+        assert_diff_applies(&a, &b);
+
+        let mut c = a.clone();
+        c.root = c.tiles.parent_of(find(&c, "b"));
+        assert_diff_applies(&a, &c);
+    }
+
+    #[test]
+    fn removed_children_leave_no_shares_behind() {
+        let mut a = Tree::new_horizontal(vec!["a", "b"]);
+        let root = a.root().expect("the tree has a root");
+        let Some(Tile::Container(Container::Linear(linear))) = a.tiles.get_mut(root) else {
+            panic!("root should be horizontal");
+        };
+        let b_id = linear.children[1];
+        linear.shares[b_id] = 3.0;
+
+        let mut b = a.clone();
+        if let Some(Tile::Container(Container::Linear(linear))) = b.tiles.get_mut(root) {
+            linear.children.retain(|&child| child != b_id);
+            linear.shares.retain(|child| child != b_id);
+        }
+        b.tiles.tiles.remove(&b_id);
+
+        let mut patched = a.clone();
+        patched.apply(&a.diff(&b));
+        let Some(Tile::Container(Container::Linear(linear))) = patched.tiles.get(root) else {
+            panic!("root should be horizontal");
+        };
+        assert!(linear.shares.into_iter().all(|(&child, _)| child != b_id));
+    }
+}
@@ -0,0 +1,403 @@
+//! A compact textual description of a layout, e.g. for default layouts in config files.
+//!
+//! See [`Tree::from_layout_string`] for the syntax.
+
+use super::{Container, Grid, GridLayout, Linear, LinearDir, Tabs, Tile, TileId, Tiles, Tree};
+
+/// An error from [`Tree::from_layout_string`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number.
+    pub line: usize,
+
+    /// 1-based column, counted in characters.
+    pub column: usize,
+
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// ----------------------------------------------------------------------------
+
+/// A child in a container, with its decorations.
+struct Item {
+    tile_id: TileId,
+    share: Option<f32>,
+    active: bool,
+
+    /// Byte offset of the start of the item, for error reporting.
+    pos: usize,
+}
+
+/// Either a plain or a quoted word.
+struct Word {
+    text: String,
+    quoted: bool,
+    pos: usize,
+}
+
+pub(super) struct Parser<'a, Pane, F> {
+    text: &'a str,
+    pos: usize,
+    tiles: Tiles<Pane>,
+    make_pane: F,
+}
+
+impl<'a, Pane, F: FnMut(&str) -> Option<Pane>> Parser<'a, Pane, F> {
+    pub(super) fn parse(text: &'a str, make_pane: F) -> Result<Tree<Pane>, ParseError> {
+        let mut parser = Self {
+            text,
+            pos: 0,
+            tiles: Tiles::default(),
+            make_pane,
+        };
+
+        parser.skip_whitespace();
+        if parser.peek().is_none() {
+            return Ok(Tree::empty());
+        }
+
+        let item = parser.parse_item()?;
+        if item.share.is_some() || item.active {
+            return Err(parser.error_at(item.pos, "the root can not have a share nor be active"));
+        }
+
+        parser.skip_whitespace();
+        if let Some(c) = parser.peek() {
+            return Err(parser.error(format!("unexpected `{c}` after the layout")));
+        }
+
+        Ok(Tree::new(item.tile_id, parser.tiles))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.pos += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> ParseError {
+        let before = &self.text[..pos];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// `[*] [share:] node`
+    fn parse_item(&mut self) -> Result<Item, ParseError> {
+        self.skip_whitespace();
+        let pos = self.pos;
+        let active = self.eat('*');
+
+        self.skip_whitespace();
+        let mut word = self.parse_word()?;
+        let mut share = None;
+
+        self.skip_whitespace();
+        if !word.quoted && self.eat(':') {
+            let value: f32 = word.text.parse().map_err(|_err| {
+                self.error_at(word.pos, format!("expected a share, found `{}`", word.text))
+            })?;
+            if !(value.is_finite() && value > 0.0) {
+                return Err(self.error_at(word.pos, "a share must be a positive number"));
+            }
+            share = Some(value);
+
+            self.skip_whitespace();
+            word = self.parse_word()?;
+        }
+
+        let tile_id = self.parse_node(&word)?;
+        Ok(Item {
+            tile_id,
+            share,
+            active,
+            pos,
+        })
+    }
+
+    fn parse_word(&mut self) -> Result<Word, ParseError> {
+        let pos = self.pos;
+
+        if self.eat('"') {
+            let mut text = String::new();
+            loop {
+                match self.peek() {
+                    None => return Err(self.error_at(pos, "unterminated string")),
+                    Some('"') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    Some('\\') => {
+                        self.pos += 1;
+                        let Some(c) = self.peek() else { continue; };
+                        text.push(c);
+                        self.pos += c.len_utf8();
+                    }
+                    Some(c) => {
+                        text.push(c);
+                        self.pos += c.len_utf8();
+                    }
+                }
+            }
+            return Ok(Word {
+                text,
+                quoted: true,
+                pos,
+            });
+        }
+
+        while let Some(c) = self.peek() {
+            if is_word_char(c) {
+                self.pos += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+        if self.pos == pos {
+            return Err(match self.peek() {
+                Some(c) => self.error(format!("expected a pane or a container, found `{c}`")),
+                None => self.error("expected a pane or a container, found the end"),
+            });
+        }
+
+        Ok(Word {
+            text: self.text[pos..self.pos].to_owned(),
+            quoted: false,
+            pos,
+        })
+    }
+
+    /// A pane name, or a container kind followed by its children.
+    fn parse_node(&mut self, word: &Word) -> Result<TileId, ParseError> {
+        self.skip_whitespace();
+        let is_container = !word.quoted && matches!(self.peek(), Some('[' | '('));
+
+        if !is_container {
+            let Some(pane) = (self.make_pane)(&word.text) else {
+                return Err(self.error_at(word.pos, format!("unknown pane `{}`", word.text)));
+            };
+            return Ok(self.tiles.insert_pane(pane));
+        }
+
+        let layout = match word.text.as_str() {
+            "grid" if self.eat('(') => {
+                self.skip_whitespace();
+                let columns = self.parse_word()?;
+                let num_columns: usize = match columns.text.parse() {
+                    Ok(num_columns) if 0 < num_columns => num_columns,
+                    _ => {
+                        return Err(self.error_at(
+                            columns.pos,
+                            format!("expected a column count, found `{}`", columns.text),
+                        ))
+                    }
+                };
+                self.skip_whitespace();
+                if !self.eat(')') {
+                    return Err(self.error("expected `)`"));
+                }
+                self.skip_whitespace();
+                GridLayout::Columns(num_columns)
+            }
+            "h" | "v" | "tabs" | "grid" => GridLayout::Auto,
+            _ => {
+                return Err(self.error_at(
+                    word.pos,
+                    format!(
+                        "unknown container `{}`, expected `h`, `v`, `tabs` or `grid`",
+                        word.text
+                    ),
+                ))
+            }
+        };
+
+        let open_pos = self.pos;
+        if !self.eat('[') {
+            return Err(self.error("expected `[`"));
+        }
+
+        let mut items = vec![];
+        loop {
+            self.skip_whitespace();
+            if self.eat(']') {
+                break;
+            }
+            if self.peek().is_none() {
+                return Err(self.error_at(open_pos, "unclosed `[`"));
+            }
+            items.push(self.parse_item()?);
+            self.skip_whitespace();
+            self.eat(',');
+        }
+
+        let container = match word.text.as_str() {
+            "h" | "v" => {
+                if let Some(item) = items.iter().find(|item| item.active) {
+                    return Err(self.error_at(item.pos, "only tabs can be marked active"));
+                }
+                let dir = if word.text == "h" {
+                    LinearDir::Horizontal
+                } else {
+                    LinearDir::Vertical
+                };
+                let mut linear = Linear::new(dir, items.iter().map(|item| item.tile_id).collect());
+                for item in &items {
+                    if let Some(share) = item.share {
+                        linear.shares[item.tile_id] = share;
+                    }
+                }
+                Container::Linear(linear)
+            }
+            "tabs" => {
+                if let Some(item) = items.iter().find(|item| item.share.is_some()) {
+                    return Err(
+                        self.error_at(item.pos, "only children of `h` and `v` can have a share")
+                    );
+                }
+                let mut active = items.iter().filter(|item| item.active);
+                let mut tabs = Tabs::new(items.iter().map(|item| item.tile_id).collect());
+                if let Some(item) = active.next() {
+                    tabs.set_active(item.tile_id);
+                }
+                if let Some(item) = active.next() {
+                    return Err(self.error_at(item.pos, "only one tab can be active"));
+                }
+                Container::Tabs(tabs)
+            }
+            _ => {
+                if let Some(item) = items
+                    .iter()
+                    .find(|item| item.share.is_some() || item.active)
+                {
+                    return Err(self.error_at(
+                        item.pos,
+                        "children of a grid can not have a share nor be active",
+                    ));
+                }
+                let mut grid = Grid::new(items.iter().map(|item| item.tile_id).collect());
+                grid.layout = layout;
+                Container::Grid(grid)
+            }
+        };
+
+        Ok(self.tiles.insert_container(container))
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
+
+// ----------------------------------------------------------------------------
+
+/// Write the given tile and its children in the layout language.
+pub(super) fn write_tile<Pane>(
+    out: &mut String,
+    tiles: &Tiles<Pane>,
+    tile_id: TileId,
+    pane_name: &mut dyn FnMut(TileId, &Pane) -> String,
+) {
+    let Some(tile) = tiles.get(tile_id) else {
+        log::warn!("Failed to find tile {tile_id:?} while writing layout");
+        return;
+    };
+
+    let container = match tile {
+        Tile::Pane(pane) => {
+            write_name(out, &pane_name(tile_id, pane));
+            return;
+        }
+        Tile::Container(container) => container,
+    };
+
+    match container {
+        Container::Tabs(_) => out.push_str("tabs"),
+        Container::Linear(linear) => match linear.dir {
+            LinearDir::Horizontal => out.push('h'),
+            LinearDir::Vertical => out.push('v'),
+        },
+        Container::Grid(grid) => match grid.layout {
+            GridLayout::Auto => out.push_str("grid"),
+            GridLayout::Columns(num_columns) => out.push_str(&format!("grid({num_columns})")),
+        },
+    }
+
+    out.push('[');
+    for (i, &child) in container.children().iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        match container {
+            Container::Tabs(tabs) => {
+                let is_first = i == 0;
+                if tabs.active == Some(child) && !is_first {
+                    out.push('*');
+                }
+            }
+            Container::Linear(linear) => {
+                let share = linear.shares[child];
+                if share != 1.0 {
+                    out.push_str(&format!("{share}:"));
+                }
+            }
+            Container::Grid(_) => {}
+        }
+        write_tile(out, tiles, child, pane_name);
+    }
+    out.push(']');
+}
+
+/// Write a pane name, quoting it if needed.
+fn write_name(out: &mut String, name: &str) {
+    let needs_quotes = name.is_empty()
+        || !name.chars().all(is_word_char)
+        || matches!(name, "h" | "v" | "tabs" | "grid")
+        || name.parse::<f32>().is_ok();
+
+    if needs_quotes {
+        out.push('"');
+        for c in name.chars() {
+            if matches!(c, '"' | '\\') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out.push('"');
+    } else {
+        out.push_str(name);
+    }
+}
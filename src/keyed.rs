@@ -0,0 +1,261 @@
+//! Looking up panes by a stable key of your choosing, instead of by [`TileId`].
+
+use std::{any::Any, collections::HashMap, hash::Hash};
+
+use egui::mutex::Mutex;
+
+use super::{Container, Tile, TileId, Tiles, Tree};
+
+/// A pane with a stable identity, e.g. "the Settings pane".
+///
+/// Implement this to look up panes with [`Tree::find_by_key`] and [`Tree::focus_key`],
+/// and to insert them with [`Tiles::insert_keyed_pane`], which rejects duplicates.
+///
+/// Panes are kept in an index by key, so lookups do not need to go through all tiles.
+///
+/// ```
+/// use egui_tiles::{KeyedPane, Tiles, Tree};
+///
+/// #[derive(Debug)]
+/// enum Pane {
+///     Settings,
+///     Document(String),
+/// }
+///
+/// impl KeyedPane for Pane {
+///     type Key = String;
+///
+///     fn key(&self) -> String {
+///         match self {
+///             Pane::Settings => "settings".to_owned(),
+///             Pane::Document(path) => path.clone(),
+///         }
+///     }
+/// }
+///
+/// let mut tiles = Tiles::default();
+/// let settings = tiles.insert_keyed_pane(Pane::Settings).unwrap();
+/// let readme = tiles.insert_keyed_pane(Pane::Document("README.md".to_owned())).unwrap();
+/// assert!(tiles.insert_keyed_pane(Pane::Settings).is_err());
+///
+/// let root = tiles.insert_tab_tile(vec![settings, readme]);
+/// let mut tree = Tree::new(root, tiles);
+///
+/// assert_eq!(tree.find_by_key(&"settings".to_owned()), Some(settings));
+/// assert!(tree.focus_key(&"settings".to_owned()));
+/// ```
+pub trait KeyedPane {
+    type Key: Eq + Hash + Send + Sync + 'static;
+
+    /// Must be unique within a tree, and must not change while the pane is in the tree.
+    fn key(&self) -> Self::Key;
+}
+
+/// Returned by [`Tiles::insert_keyed_pane`] when there already is a pane with the same key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateKey<Pane> {
+    /// The tile that already has the key.
+    pub existing: TileId,
+
+    /// The pane that was not inserted.
+    pub pane: Pane,
+}
+
+impl<Pane> std::fmt::Display for DuplicateKey<Pane> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the tree already has a pane with this key: {:?}",
+            self.existing
+        )
+    }
+}
+
+impl<Pane: std::fmt::Debug> std::error::Error for DuplicateKey<Pane> {}
+
+// ----------------------------------------------------------------------------
+
+/// Maps each [`KeyedPane::Key`] to the tile of that pane.
+///
+/// Built on first use, and kept up to date by [`Tiles::insert_keyed_pane`].
+/// Inserting a pane any other way throws it away, to be rebuilt on the next lookup.
+/// Entries for removed tiles are dropped when they are looked up.
+///
+/// [`Tiles`] is not generic over the key type, so the map is type-erased.
+#[derive(Default)]
+pub(super) struct KeyIndex(Mutex<Option<Box<dyn Any + Send + Sync>>>);
+
+impl KeyIndex {
+    pub(super) fn invalidate(&self) {
+        *self.0.lock() = None;
+    }
+}
+
+/// The index is only a cache, so a clone starts out without one.
+impl Clone for KeyIndex {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// A cache does not affect equality.
+impl PartialEq for KeyIndex {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl std::fmt::Debug for KeyIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyIndex").finish_non_exhaustive()
+    }
+}
+
+impl<Pane: KeyedPane> Tiles<Pane> {
+    /// The tile of the pane with the given key, if any.
+    ///
+    /// This is a hash map lookup, except right after panes were inserted without
+    /// [`Self::insert_keyed_pane`], when the index is rebuilt with a scan of all tiles.
+    pub fn find_by_key(&self, key: &Pane::Key) -> Option<TileId> {
+        self.with_key_map(|map| {
+            let tile_id = *map.get(key)?;
+            if matches!(self.get(tile_id), Some(Tile::Pane(pane)) if &pane.key() == key) {
+                Some(tile_id)
+            } else {
+                // The tile was removed (or replaced) since it was indexed:
+                map.remove(key);
+                None
+            }
+        })
+    }
+
+    /// Insert a pane, unless there already is a pane with the same key.
+    ///
+    /// Like [`Self::insert_pane`], it is up to you to add the returned tile to a container.
+    ///
+    /// # Errors
+    /// If there already is a pane with the same key, the pane is handed back together with the existing tile.
+    pub fn insert_keyed_pane(&mut self, pane: Pane) -> Result<TileId, DuplicateKey<Pane>> {
+        let key = pane.key();
+        if let Some(existing) = self.find_by_key(&key) {
+            return Err(DuplicateKey { existing, pane });
+        }
+
+        // Not `insert_pane`, which would throw away the index:
+        let tile_id = self.next_free_id(|_| false);
+        self.tiles.insert(tile_id, Tile::Pane(pane));
+        self.with_key_map(|map| map.insert(key, tile_id));
+        Ok(tile_id)
+    }
+
+    fn with_key_map<R>(&self, f: impl FnOnce(&mut HashMap<Pane::Key, TileId>) -> R) -> R {
+        let mut index = self.key_index.0.lock();
+        let map = index
+            .get_or_insert_with(|| {
+                let mut map = HashMap::<Pane::Key, TileId>::default();
+                for (&tile_id, tile) in &self.tiles {
+                    if let Tile::Pane(pane) = tile {
+                        map.insert(pane.key(), tile_id);
+                    }
+                }
+                Box::new(map)
+            })
+            .downcast_mut()
+            .expect("the key type is decided by the pane type, so it never changes");
+        f(map)
+    }
+}
+
+impl<Pane: KeyedPane> Tree<Pane> {
+    /// The tile of the pane with the given key, if any.
+    ///
+    /// See [`Tiles::find_by_key`].
+    pub fn find_by_key(&self, key: &Pane::Key) -> Option<TileId> {
+        self.tiles.find_by_key(key)
+    }
+
+    /// Make the pane with the given key visible, by making it and its ancestors the active tabs.
+    ///
+    /// Returns `false` if there is no such pane.
+    pub fn focus_key(&mut self, key: &Pane::Key) -> bool {
+        let Some(mut child) = self.find_by_key(key) else { return false; };
+        while let Some(parent) = self.tiles.parent_of(child) {
+            if let Some(Tile::Container(Container::Tabs(tabs))) = self.tiles.get_mut(parent) {
+                tabs.set_active(child);
+            }
+            child = parent;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Keys that all have the same hash.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct CollidingKey(u32);
+
+    impl Hash for CollidingKey {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            state.write_u32(0);
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Pane(u32);
+
+    impl KeyedPane for Pane {
+        type Key = CollidingKey;
+
+        fn key(&self) -> CollidingKey {
+            CollidingKey(self.0)
+        }
+    }
+
+    #[test]
+    fn hash_collisions_are_different_keys() {
+        let mut tiles = Tiles::default();
+        let a = tiles.insert_keyed_pane(Pane(1)).expect("new key");
+        let b = tiles.insert_keyed_pane(Pane(2)).expect("new key");
+        assert_ne!(a, b);
+        assert_eq!(tiles.find_by_key(&CollidingKey(1)), Some(a));
+        assert_eq!(tiles.find_by_key(&CollidingKey(2)), Some(b));
+        assert_eq!(tiles.find_by_key(&CollidingKey(3)), None);
+        assert!(tiles.insert_keyed_pane(Pane(2)).is_err());
+    }
+
+    #[test]
+    fn index_follows_other_inserts_and_removals() {
+        let mut tiles = Tiles::default();
+        let a = tiles.insert_keyed_pane(Pane(1)).expect("new key");
+        let b = tiles.insert_pane(Pane(2));
+        assert_eq!(tiles.find_by_key(&CollidingKey(2)), Some(b));
+
+        tiles.tiles.remove(&a);
+        assert_eq!(tiles.find_by_key(&CollidingKey(1)), None);
+        assert!(tiles.insert_keyed_pane(Pane(1)).is_ok());
+    }
+
+    #[test]
+    fn index_follows_panes_moved_into_new_tabs() {
+        let mut tiles = Tiles::default();
+        let a = tiles.insert_keyed_pane(Pane(1)).expect("new key");
+        let b = tiles.insert_keyed_pane(Pane(2)).expect("new key");
+        let root = tiles.insert_horizontal_tile(vec![a, b]);
+        let mut tree = Tree::new(root, tiles);
+        assert_eq!(tree.find_by_key(&CollidingKey(1)), Some(a));
+
+        tree.simplify(&crate::SimplificationOptions {
+            all_panes_must_have_tabs: true,
+            ..Default::default()
+        });
+
+        let a = tree
+            .find_by_key(&CollidingKey(1))
+            .expect("a is still in the tree");
+        assert_eq!(tree.tiles.get(a), Some(&Tile::Pane(Pane(1))));
+        assert!(tree.focus_key(&CollidingKey(2)));
+    }
+}
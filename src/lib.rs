@@ -7,7 +7,7 @@
 //! The [`Tile`]s are put into a [`Tree`].
 //! Everything is generic over the type of panes, leaving up to the user what to store in the tree.
 //!
-//! Each [`Tile`] is identified by a (random) [`TileId`].
+//! Each [`Tile`] is identified by a (random) [`TileId`], picked by the [`IdAllocator`] of the [`Tiles`].
 //! The tiles are stored in [`Tiles`].
 //!
 //! The entire state is stored in a single [`Tree`] struct which consists of a [`Tiles`] and a root [`TileId`].
@@ -82,6 +82,10 @@
 //! the total shares are always approximately the same as the number of rows/columns.
 //! This makes it easy to add new rows/columns.
 //!
+//! ## Feature flags
+//! * `rand` (default): use [`rand`](https://docs.rs/rand) for random [`TileId`]s.
+//!   Turn it off to avoid depending on `getrandom`, e.g. on the web.
+//!
 //! ## Shortcomings
 //! The implementation is recursive, so if your trees get too deep you will get a stack overflow.
 //!
@@ -112,16 +116,40 @@
 use egui::{Pos2, Rect};
 
 mod behavior;
+mod compass;
 mod container;
+mod diff;
+mod dsl;
+mod keyed;
+mod payload;
+mod placement;
+mod serialized;
 mod tile;
 mod tiles;
+mod tiling;
+mod transfer;
+mod traversal;
 mod tree;
+mod validate;
+mod workspaces;
 
 pub use behavior::Behavior;
+pub use compass::{CompassButton, DropTargetMode};
 pub use container::{Container, ContainerKind, Grid, GridLayout, GridLoc, Linear, LinearDir, Tabs};
-pub use tile::{Tile, TileId};
+pub use diff::{TileChange, TreeDiff};
+pub use dsl::ParseError;
+pub use keyed::{DuplicateKey, KeyedPane};
+pub use payload::DragPayload;
+pub use placement::Placement;
+pub use serialized::{GridChildV1, LinearChildV1, SerializedTree, TileV1, TreeV1};
+pub use tile::{IdAllocator, Tile, TileId};
 pub use tiles::Tiles;
+pub use tiling::TilingStrategy;
+pub use transfer::{transfer_between_trees, TreeTransfer};
+pub use traversal::{Visitor, VisitorMut};
 pub use tree::Tree;
+pub use validate::TreeError;
+pub use workspaces::Workspaces;
 
 // ----------------------------------------------------------------------------
 
@@ -141,7 +169,7 @@ pub enum UiResponse {
 /// Drag-dropping tiles can often leave containers empty, or with only a single child.
 /// The [`SimplificationOptions`] specifies what simplifications are allowed.
 ///
-/// The [`Tree`] will run a simplification pass each frame.
+/// When the [`Tree`] runs a simplification pass is decided by [`SimplificationMode`].
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct SimplificationOptions {
     /// Remove empty [`Tabs`] containers?
@@ -181,6 +209,23 @@ impl Default for SimplificationOptions {
     }
 }
 
+/// When [`Tree::ui`] simplifies the tree, using [`Behavior::simplification_options`].
+///
+/// This does not affect [`Tree::gc`], which [`Tree::ui`] runs every frame,
+/// so that panes closed with [`Behavior::retain_pane`] go away right away.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SimplificationMode {
+    /// Simplify at the start of each frame.
+    #[default]
+    EveryFrame,
+
+    /// Simplify only when tiles have been added, removed or moved since the last simplification.
+    AfterEdits,
+
+    /// Never simplify during [`Tree::ui`]; call [`Tree::simplify`] yourself.
+    Manual,
+}
+
 /// The current state of a resize handle.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ResizeState {
@@ -198,8 +243,11 @@ pub enum ResizeState {
 /// An insertion point in a specific container.
 ///
 /// Specifies the expected container layout type, and where to insert.
+/// If the parent is not of the expected type, it will be wrapped in a new container that is.
+///
+/// An index of `usize::MAX` means "at the end".
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum ContainerInsertion {
+pub enum ContainerInsertion {
     Tabs(usize),
     Horizontal(usize),
     Vertical(usize),
@@ -207,8 +255,11 @@ enum ContainerInsertion {
 }
 
 /// Where in the tree to insert a tile.
-#[derive(Clone, Copy, Debug)]
-struct InsertionPoint {
+///
+/// This is what is suggested while drag-and-dropping,
+/// and is passed to [`Behavior::can_drop`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InsertionPoint {
     pub parent_id: TileId,
 
     /// Where in the parent?
@@ -269,14 +320,36 @@ fn cover_tile_if_dragged<Pane>(
 struct DropContext {
     enabled: bool,
     dragged_tile_id: Option<TileId>,
+
+    /// Something from outside the tree being dragged, and the id of its egui drag.
+    payload: Option<(egui::Id, DragPayload)>,
+
+    /// Are files from the operating system hovering over us (or being dropped)?
+    dragging_files: bool,
+
     mouse_pos: Option<Pos2>,
 
+    /// All the places the dragged tile could go, and how to preview them.
+    ///
+    /// Collected during the `ui` pass, and then filtered with [`Behavior::can_drop`].
+    candidates: Vec<(InsertionPoint, Rect)>,
+
+    /// The innermost tile under the mouse, used by [`DropTargetMode::Compass`].
+    hovered_tile: Option<(TileId, Rect)>,
+
+    /// The allowed compass buttons, if [`DropTargetMode::Compass`] is used.
+    compass: Vec<compass::CompassTarget>,
+
     best_insertion: Option<InsertionPoint>,
-    best_dist_sq: f32,
     preview_rect: Option<Rect>,
 }
 
 impl DropContext {
+    /// Is a tile, an external [`DragPayload`], or a file being dragged?
+    fn is_dragging(&self) -> bool {
+        self.dragged_tile_id.is_some() || self.payload.is_some() || self.dragging_files
+    }
+
     fn on_tile<Pane>(
         &mut self,
         behavior: &mut dyn Behavior<Pane>,
@@ -284,11 +357,28 @@ impl DropContext {
         parent_id: TileId,
         rect: Rect,
         tile: &Tile<Pane>,
+        in_tabs: bool,
     ) {
         if !self.enabled {
             return;
         }
 
+        // A pane in a tab container fills it, so let the compass target the tabs instead.
+        if let Some(mouse_pos) = self.mouse_pos {
+            if !in_tabs && rect.contains(mouse_pos) {
+                self.hovered_tile = Some((parent_id, rect));
+            }
+        }
+
+        if matches!(tile, Tile::Container(container) if container.is_placeholder()) {
+            // The only thing to do with a placeholder is to fill it:
+            self.suggest_rect(
+                InsertionPoint::new(parent_id, ContainerInsertion::Tabs(0)),
+                rect,
+            );
+            return;
+        }
+
         if tile.kind() != Some(ContainerKind::Horizontal) {
             self.suggest_rect(
                 InsertionPoint::new(parent_id, ContainerInsertion::Horizontal(0)),
@@ -322,11 +412,43 @@ impl DropContext {
         if !self.enabled {
             return;
         }
-        let target_point = preview_rect.center();
-        if let Some(mouse_pos) = self.mouse_pos {
-            let dist_sq = mouse_pos.distance_sq(target_point);
-            if dist_sq < self.best_dist_sq {
-                self.best_dist_sq = dist_sq;
+        if self.mouse_pos.is_some() {
+            self.candidates.push((insertion, preview_rect));
+        }
+    }
+
+    /// Only keep the allowed compass buttons, and pick the one under the mouse.
+    fn pick_compass(
+        &mut self,
+        targets: Vec<compass::CompassTarget>,
+        mut is_allowed: impl FnMut(&InsertionPoint) -> bool,
+    ) {
+        self.candidates.clear();
+        self.compass = targets
+            .into_iter()
+            .filter(|target| is_allowed(&target.insertion))
+            .collect();
+
+        let Some(mouse_pos) = self.mouse_pos else { return; };
+        if let Some(target) = self
+            .compass
+            .iter()
+            .find(|target| target.button_rect.contains(mouse_pos))
+        {
+            self.best_insertion = Some(target.insertion);
+            self.preview_rect = Some(target.preview_rect);
+        }
+    }
+
+    /// Pick the allowed candidate closest to the mouse.
+    fn pick_best(&mut self, mut is_allowed: impl FnMut(&InsertionPoint) -> bool) {
+        let Some(mouse_pos) = self.mouse_pos else { return; };
+
+        let mut best_dist_sq = f32::INFINITY;
+        for (insertion, preview_rect) in std::mem::take(&mut self.candidates) {
+            let dist_sq = mouse_pos.distance_sq(preview_rect.center());
+            if dist_sq < best_dist_sq && is_allowed(&insertion) {
+                best_dist_sq = dist_sq;
                 self.best_insertion = Some(insertion);
                 self.preview_rect = Some(preview_rect);
             }
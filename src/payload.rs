@@ -0,0 +1,67 @@
+use std::{any::Any, sync::Arc};
+
+use egui::{Context, Id};
+
+/// Something from outside the tree that can be drag-and-dropped into it,
+/// e.g. an asset from an asset browser.
+///
+/// Start an egui drag as usual, then register the payload with [`Self::set`].
+/// While the drag is active, the [`crate::Tree`] shows drop zones for it,
+/// and on release calls [`crate::Behavior::create_pane_from_drop`].
+///
+/// ```
+/// # let ctx = egui::Context::default();
+/// # let _ = ctx.run(Default::default(), |ctx| {
+/// # egui::CentralPanel::default().show(ctx, |ui| {
+/// let response = ui.add(egui::Label::new("my_image.png").sense(egui::Sense::drag()));
+/// if response.drag_started() {
+///     egui_tiles::DragPayload::set(ui.ctx(), response.id, String::from("my_image.png"));
+/// }
+/// # });
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct DragPayload(Arc<dyn Any + Send + Sync>);
+
+impl std::fmt::Debug for DragPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DragPayload").finish_non_exhaustive()
+    }
+}
+
+impl DragPayload {
+    pub fn new(value: impl Any + Send + Sync) -> Self {
+        Self(Arc::new(value))
+    }
+
+    /// The value given to [`Self::new`], if it is of type `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+
+    fn storage_id() -> Id {
+        Id::new("egui_tiles_drag_payload")
+    }
+
+    /// Register what is being dragged by the egui drag with the given id,
+    /// usually the [`egui::Response::id`] of the widget being dragged.
+    pub fn set(ctx: &Context, drag_id: Id, value: impl Any + Send + Sync) {
+        let payload = Self::new(value);
+        ctx.data_mut(|data| data.insert_temp(Self::storage_id(), (drag_id, payload)));
+    }
+
+    /// The payload of the current drag, if any.
+    pub(super) fn dragged(ctx: &Context) -> Option<(Id, Self)> {
+        if !super::is_possible_drag(ctx) {
+            return None;
+        }
+        let (drag_id, payload): (Id, Self) =
+            ctx.data_mut(|data| data.get_temp(Self::storage_id()))?;
+        if ctx.memory(|mem| mem.is_being_dragged(drag_id)) {
+            Some((drag_id, payload))
+        } else {
+            ctx.data_mut(|data| data.remove::<(Id, Self)>(Self::storage_id()));
+            None
+        }
+    }
+}
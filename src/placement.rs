@@ -0,0 +1,217 @@
+//! Opening panes next to existing tiles, without building an [`crate::InsertionPoint`] by hand.
+
+use super::{
+    Container, ContainerInsertion, ContainerKind, InsertionPoint, SimplifyAction, Tile, TileId,
+    Tree,
+};
+
+/// Where [`Tree::open_beside`] puts a new pane, relative to an existing tile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Placement {
+    /// As the next tab after it, turning it into a tab if it isn't one already.
+    TabAfter,
+    Left,
+    Right,
+    Above,
+    Below,
+}
+
+impl<Pane> Tree<Pane> {
+    /// Open a new pane next to the `anchor` tile, and return the id of the new pane.
+    ///
+    /// The result is the same as dropping the pane on that side of the anchor:
+    /// a new tab is added to the tabs containing the anchor and made active,
+    /// and a split is added to a horizontal or vertical parent of the anchor if it has the right direction.
+    /// Otherwise the pane is added to the anchor if it is a container of the right kind,
+    /// or else the anchor is wrapped in a new container, which takes its place.
+    /// Unlike when dropping, the anchor keeps its id either way.
+    ///
+    /// For splits, the new pane gets `fraction` of the space the anchor had, clamped to `0.05..=0.95`.
+    ///
+    /// Returns `None`, and drops the pane, if the anchor is not in the tree.
+    ///
+    /// ```
+    /// use egui_tiles::{Placement, Tree};
+    ///
+    /// let mut tree = Tree::from_layout_string("h[a, b]", |name| Some(name.to_owned())).unwrap();
+    /// let find = |tree: &Tree<String>, name: &str| {
+    ///     tree.find(|_, tile| matches!(tile, egui_tiles::Tile::Pane(pane) if pane == name)).unwrap()
+    /// };
+    ///
+    /// tree.open_beside(find(&tree, "b"), "c".to_owned(), Placement::Right, 0.3);
+    /// assert_eq!(tree.to_layout_string(|pane| pane.clone()), "h[a, 0.7:b, 0.3:c]");
+    ///
+    /// tree.open_beside(find(&tree, "a"), "d".to_owned(), Placement::Below, 0.5);
+    /// assert_eq!(tree.to_layout_string(|pane| pane.clone()), "h[v[0.5:a, 0.5:d], 0.7:b, 0.3:c]");
+    ///
+    /// tree.open_beside(find(&tree, "c"), "e".to_owned(), Placement::TabAfter, 0.0);
+    /// assert_eq!(tree.to_layout_string(|pane| pane.clone()), "h[v[0.5:a, 0.5:d], 0.7:b, 0.3:tabs[c, *e]]");
+    ///
+    /// tree.open_beside(find(&tree, "c"), "f".to_owned(), Placement::TabAfter, 0.0);
+    /// assert_eq!(tree.to_layout_string(|pane| pane.clone()), "h[v[0.5:a, 0.5:d], 0.7:b, 0.3:tabs[c, *f, e]]");
+    /// ```
+    pub fn open_beside(
+        &mut self,
+        anchor: TileId,
+        pane: Pane,
+        placement: Placement,
+        fraction: f32,
+    ) -> Option<TileId> {
+        if self.tiles.get(anchor).is_none() {
+            log::warn!("Failed to open a pane beside {anchor:?}: no such tile");
+            return None;
+        }
+        let fraction = fraction.clamp(0.05, 0.95);
+        let kind = match placement {
+            Placement::TabAfter => ContainerKind::Tabs,
+            Placement::Left | Placement::Right => ContainerKind::Horizontal,
+            Placement::Above | Placement::Below => ContainerKind::Vertical,
+        };
+        let after = matches!(
+            placement,
+            Placement::TabAfter | Placement::Right | Placement::Below
+        );
+        let children_if_kind = |tree: &Self, tile_id: TileId| match tree.tiles.get(tile_id) {
+            Some(Tile::Container(container)) if container.kind() == kind => {
+                Some(container.children().to_vec())
+            }
+            _ => None,
+        };
+
+        // Pick the insertion point the same way a drop beside the anchor does:
+        let parent_id = self.tiles.parent_of(anchor);
+        let (target, index) = match parent_id.and_then(|id| Some((id, children_if_kind(self, id)?)))
+        {
+            Some((parent_id, siblings)) => {
+                (parent_id, index_of(&siblings, anchor) + usize::from(after))
+            }
+            None => match children_if_kind(self, anchor) {
+                Some(children) => (anchor, if after { children.len() } else { 0 }),
+                None => (anchor, usize::from(after)),
+            },
+        };
+        let wraps_anchor = target == anchor && children_if_kind(self, anchor).is_none();
+        let insertion = match placement {
+            Placement::TabAfter => ContainerInsertion::Tabs(index),
+            Placement::Left | Placement::Right => ContainerInsertion::Horizontal(index),
+            Placement::Above | Placement::Below => ContainerInsertion::Vertical(index),
+        };
+
+        let new = self.tiles.insert_pane(pane);
+        self.tiles
+            .insert(InsertionPoint::new(target, insertion), new);
+        if wraps_anchor {
+            self.give_back_id(anchor, parent_id, new);
+        }
+
+        if kind != ContainerKind::Tabs {
+            let container = self.tiles.parent_of(new);
+            if let Some(Tile::Container(Container::Linear(linear))) =
+                container.and_then(|id| self.tiles.get_mut(id))
+            {
+                if linear.children.contains(&anchor) {
+                    let share = linear.shares[anchor];
+                    linear.shares[anchor] = share * (1.0 - fraction);
+                    linear.shares[new] = share * fraction;
+                } else {
+                    // Added inside the anchor, so take the fraction from all of it:
+                    let others: f32 = linear
+                        .children
+                        .iter()
+                        .filter(|&&child| child != new)
+                        .map(|&child| linear.shares[child])
+                        .sum();
+                    linear.shares[new] = others * fraction / (1.0 - fraction);
+                }
+            }
+        }
+        Some(new)
+    }
+
+    /// [`crate::Tiles`] wraps a tile by moving it to a new id and putting the wrapper in its place.
+    /// Swap the two back, so that the wrapped tile keeps its id.
+    fn give_back_id(&mut self, wrapped: TileId, parent_id: Option<TileId>, new: TileId) {
+        let moved_to = match self.tiles.get(wrapped) {
+            Some(Tile::Container(wrapper)) => wrapper
+                .children()
+                .iter()
+                .copied()
+                .find(|&child| child != new),
+            _ => None,
+        };
+        let Some(moved_to) = moved_to else {
+            log::warn!("Expected {wrapped:?} to have been wrapped in a container");
+            return;
+        };
+        let (Some(Tile::Container(mut wrapper)), Some(tile)) = (
+            self.tiles.tiles.remove(&wrapped),
+            self.tiles.tiles.remove(&moved_to),
+        ) else {
+            return; // Checked above
+        };
+
+        wrapper.simplify_children(|child| {
+            if child == moved_to {
+                SimplifyAction::Replace(wrapped)
+            } else {
+                SimplifyAction::Keep
+            }
+        });
+        self.tiles.key_index.invalidate(); // The wrapped tile may be a pane
+        self.tiles.tiles.insert(wrapped, tile);
+        self.tiles.tiles.insert(moved_to, Tile::Container(wrapper));
+        self.replace_child(parent_id, wrapped, moved_to);
+    }
+}
+
+fn index_of(children: &[TileId], child: TileId) -> usize {
+    children
+        .iter()
+        .position(|&it| it == child)
+        .unwrap_or(children.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IdAllocator, Tiles};
+
+    #[test]
+    fn wrapping_never_reuses_the_id_of_the_wrapped_tile() {
+        let mut tiles = Tiles {
+            id_allocator: IdAllocator::Sequential { next: 2 },
+            ..Default::default()
+        };
+        let a = tiles.insert_pane("a");
+        let b = tiles.insert_pane("b");
+        let root = tiles.insert_horizontal_tile(vec![a, b]);
+        tiles.id_allocator = IdAllocator::sequential();
+        let mut tree = Tree::new(root, tiles);
+
+        tree.open_beside(a, "c", Placement::Below, 0.5);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(
+            tree.to_layout_string(|pane| (*pane).to_owned()),
+            "h[v[0.5:a, 0.5:c], b]"
+        );
+    }
+
+    #[test]
+    fn wrapping_the_root_keeps_the_anchor_id() {
+        let mut tiles = Tiles::default();
+        let a = tiles.insert_pane("a");
+        let mut tree = Tree::new(a, tiles);
+
+        let b = tree
+            .open_beside(a, "b", Placement::Below, 2.0)
+            .expect("a is in the tree");
+        assert_eq!(tree.tiles.get(a), Some(&Tile::Pane("a")));
+        let Some(Tile::Container(Container::Linear(linear))) =
+            tree.root().and_then(|root| tree.tiles.get(root))
+        else {
+            panic!("root should be a vertical split");
+        };
+        assert_eq!(linear.children, [a, b]);
+        assert_eq!(linear.shares[b], 0.95);
+    }
+}
@@ -0,0 +1,668 @@
+//! An explicit, versioned format for saving and loading a [`Tree`].
+//!
+//! [`Tree`] and friends derive serde directly, which makes that format change whenever
+//! their internals do. [`SerializedTree`] is instead a stable format with a version tag,
+//! and old versions are migrated to the latest one when loading.
+//!
+//! ```
+//! use egui_tiles::{SerializedTree, Tree};
+//!
+//! let tree = Tree::new_tabs(vec!["a".to_owned(), "b".to_owned()]);
+//! let json = serde_json::to_string(&tree.to_serialized()).unwrap();
+//!
+//! let loaded: SerializedTree<String> = serde_json::from_str(&json).unwrap();
+//! let loaded = Tree::from_serialized(loaded);
+//! ```
+//!
+//! Layouts saved with the plain serde derive of [`Tree`] (version 0) can still be loaded.
+//!
+//! Unknown fields and tile kinds are ignored, and references to missing tiles are dropped:
+//!
+//! ```
+//! use egui_tiles::{SerializedTree, Tree};
+//!
+//! let json = r#"{
+//!     "version": "1",
+//!     "root": 1,
+//!     "some_future_field": true,
+//!     "tiles": [
+//!         { "kind": "Tabs", "id": 1, "children": [2, 3, 4], "active": 3 },
+//!         { "kind": "Pane", "id": 2, "pane": "hello", "pinned": true },
+//!         { "kind": "SomeFutureKind", "id": 3 }
+//!     ]
+//! }"#;
+//! let loaded: SerializedTree<String> = serde_json::from_str(json).unwrap();
+//! let tree = Tree::from_serialized(loaded);
+//! assert_eq!(tree.tiles.tiles.len(), 2);
+//! ```
+
+use serde::{
+    de::{value::MapAccessDeserializer, DeserializeSeed, IntoDeserializer, MapAccess},
+    Deserialize, Serialize,
+};
+
+use super::{
+    Container, Grid, GridLayout, GridLoc, Linear, LinearDir, Tabs, Tile, TileId, Tiles,
+    TilingStrategy, Tree,
+};
+
+/// A [`Tree`] in a versioned format, for saving and loading.
+///
+/// Serializing always writes the latest version, with a `"version"` tag.
+/// Deserializing accepts any known version, as well as the untagged
+/// legacy format of [`Tree`] (treated as version 0).
+/// The `"version"` tag must be the first field, as it is when written by serde.
+///
+/// Use [`Tree::to_serialized`] and [`Tree::from_serialized`] to convert.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "version")]
+pub enum SerializedTree<Pane> {
+    /// The format of the serde derives of [`Tree`].
+    #[serde(rename = "0")]
+    V0(Tree<Pane>),
+
+    /// A flat list of tiles, see [`TreeV1`].
+    #[serde(rename = "1")]
+    V1(TreeV1<Pane>),
+}
+
+impl<'de, Pane: Deserialize<'de>> Deserialize<'de> for SerializedTree<Pane> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(SerializedTreeVisitor(std::marker::PhantomData))
+    }
+}
+
+#[derive(Deserialize)]
+enum Version {
+    #[serde(rename = "0")]
+    V0,
+    #[serde(rename = "1")]
+    V1,
+}
+
+/// Reads the `version` tag before the rest, so that the rest does not need to be buffered,
+/// and errors in it point to where they are.
+struct SerializedTreeVisitor<Pane>(std::marker::PhantomData<Pane>);
+
+impl<'de, Pane: Deserialize<'de>> serde::de::Visitor<'de> for SerializedTreeVisitor<Pane> {
+    type Value = SerializedTree<Pane>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("a tree")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let first_key: Option<String> = map.next_key()?;
+        if first_key.as_deref() == Some("version") {
+            let version: Version = map.next_value()?;
+            let rest = MapAccessDeserializer::new(map);
+            return match version {
+                Version::V0 => Tree::deserialize(rest).map(SerializedTree::V0),
+                Version::V1 => TreeV1::deserialize(rest).map(SerializedTree::V1),
+            };
+        }
+
+        // No tag, so this is the legacy format of [`Tree`]:
+        let rest = MapAccessDeserializer::new(LegacyMap { first_key, map });
+        Tree::deserialize(rest).map(SerializedTree::V0)
+    }
+}
+
+/// The fields of a map without a `version` tag, starting with the one that was already read.
+struct LegacyMap<A> {
+    first_key: Option<String>,
+    map: A,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for LegacyMap<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        let key = match self.first_key.take() {
+            Some(key) => key,
+            None => match self.map.next_key::<String>()? {
+                Some(key) => key,
+                None => return Ok(None),
+            },
+        };
+        if key == "version" {
+            return Err(serde::de::Error::custom(
+                "the `version` tag must be the first field",
+            ));
+        }
+        seed.deserialize(IntoDeserializer::<A::Error>::into_deserializer(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, A::Error> {
+        self.map.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.map.size_hint()
+    }
+}
+
+impl<Pane> SerializedTree<Pane> {
+    /// The version written by [`Tree::to_serialized`].
+    pub const LATEST_VERSION: u32 = 1;
+
+    pub fn version(&self) -> u32 {
+        match self {
+            Self::V0(_) => 0,
+            Self::V1(_) => 1,
+        }
+    }
+
+    /// Migrate to the latest version.
+    pub fn into_latest(self) -> TreeV1<Pane> {
+        match self {
+            Self::V0(tree) => migrate_v0_to_v1(tree),
+            Self::V1(tree) => tree,
+        }
+    }
+}
+
+fn migrate_v0_to_v1<Pane>(tree: Tree<Pane>) -> TreeV1<Pane> {
+    let skeleton = TreeV1::from_tree(&tree).map_panes(|_, _| Some(()));
+    let mut tiles = tree.tiles.tiles;
+    skeleton.map_panes(|tile_id, ()| match tiles.remove(&tile_id) {
+        Some(Tile::Pane(pane)) => Some(pane),
+        _ => None,
+    })
+}
+
+// ----------------------------------------------------------------------------
+
+/// Version 1 of the [`SerializedTree`] format: a flat list of tiles, parents before children.
+///
+/// Sizes are stored next to each child, instead of in maps keyed by [`TileId`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TreeV1<Pane> {
+    #[serde(default)]
+    pub root: Option<TileId>,
+
+    #[serde(default = "Vec::new")]
+    pub tiles: Vec<TileV1<Pane>>,
+}
+
+/// A tile in [`TreeV1`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum TileV1<Pane> {
+    Pane {
+        id: TileId,
+        pane: Pane,
+    },
+    Tabs {
+        id: TileId,
+        #[serde(default)]
+        children: Vec<TileId>,
+        #[serde(default)]
+        active: Option<TileId>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        keep_if_empty: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        placeholder: Option<String>,
+    },
+    Horizontal {
+        id: TileId,
+        #[serde(default)]
+        children: Vec<LinearChildV1>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        keep_if_empty: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        auto_tiling: Option<TilingStrategy>,
+    },
+    Vertical {
+        id: TileId,
+        #[serde(default)]
+        children: Vec<LinearChildV1>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        keep_if_empty: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        auto_tiling: Option<TilingStrategy>,
+    },
+    Grid {
+        id: TileId,
+        #[serde(default)]
+        children: Vec<GridChildV1>,
+        #[serde(default)]
+        layout: GridLayout,
+        #[serde(default)]
+        col_shares: Vec<f32>,
+        #[serde(default)]
+        row_shares: Vec<f32>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        keep_if_empty: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        auto_tiling: Option<TilingStrategy>,
+    },
+
+    /// A kind of tile written by a newer version. Skipped when loading.
+    #[serde(other)]
+    Unknown,
+}
+
+/// A child of a horizontal or vertical container in [`TreeV1`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinearChildV1 {
+    pub id: TileId,
+
+    #[serde(default = "default_share")]
+    pub share: f32,
+}
+
+fn default_share() -> f32 {
+    1.0
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // required by serde
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// A child of a grid container in [`TreeV1`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GridChildV1 {
+    pub id: TileId,
+
+    /// If missing, the child is assigned a location during layout.
+    #[serde(default)]
+    pub loc: Option<GridLoc>,
+}
+
+impl<Pane> TileV1<Pane> {
+    pub fn id(&self) -> Option<TileId> {
+        match self {
+            Self::Pane { id, .. }
+            | Self::Tabs { id, .. }
+            | Self::Horizontal { id, .. }
+            | Self::Vertical { id, .. }
+            | Self::Grid { id, .. } => Some(*id),
+            Self::Unknown => None,
+        }
+    }
+}
+
+impl<'a, Pane> TreeV1<&'a Pane> {
+    /// All tiles reachable from the root, parents before children.
+    pub fn from_tree(tree: &'a Tree<Pane>) -> Self {
+        let mut visited = nohash_hasher::IntSet::default();
+        let mut tiles = vec![];
+        let mut stack: Vec<TileId> = tree.root.into_iter().collect();
+
+        while let Some(tile_id) = stack.pop() {
+            if !visited.insert(tile_id) {
+                log::warn!("Tile {tile_id:?} appears more than once in the tree");
+                continue;
+            }
+            let Some(tile) = tree.tiles.get(tile_id) else {
+                log::warn!("Failed to find tile {tile_id:?} while serializing");
+                continue;
+            };
+            let id = tile_id;
+            tiles.push(match tile {
+                Tile::Pane(pane) => TileV1::Pane { id, pane },
+                Tile::Container(Container::Tabs(tabs)) => TileV1::Tabs {
+                    id,
+                    children: tabs.children.clone(),
+                    active: tabs.active,
+                    keep_if_empty: tabs.keep_if_empty,
+                    placeholder: tabs.placeholder.clone(),
+                },
+                Tile::Container(Container::Linear(linear)) => {
+                    let children = linear
+                        .children
+                        .iter()
+                        .map(|&child| LinearChildV1 {
+                            id: child,
+                            share: linear.shares[child],
+                        })
+                        .collect();
+                    let keep_if_empty = linear.keep_if_empty;
+                    let auto_tiling = linear.auto_tiling;
+                    match linear.dir {
+                        LinearDir::Horizontal => TileV1::Horizontal {
+                            id,
+                            children,
+                            keep_if_empty,
+                            auto_tiling,
+                        },
+                        LinearDir::Vertical => TileV1::Vertical {
+                            id,
+                            children,
+                            keep_if_empty,
+                            auto_tiling,
+                        },
+                    }
+                }
+                Tile::Container(Container::Grid(grid)) => TileV1::Grid {
+                    id,
+                    children: grid
+                        .children
+                        .iter()
+                        .map(|&child| GridChildV1 {
+                            id: child,
+                            loc: grid.locations.get(&child).copied(),
+                        })
+                        .collect(),
+                    layout: grid.layout,
+                    col_shares: grid.col_shares.clone(),
+                    row_shares: grid.row_shares.clone(),
+                    keep_if_empty: grid.keep_if_empty,
+                    auto_tiling: grid.auto_tiling,
+                },
+            });
+            if let Tile::Container(container) = tile {
+                stack.extend(container.children().iter().rev());
+            }
+        }
+
+        Self {
+            root: tree.root,
+            tiles,
+        }
+    }
+}
+
+impl<Pane> TreeV1<Pane> {
+    /// Convert each pane, dropping those for which `f` returns `None`.
+    ///
+    /// References to dropped panes are removed by [`Self::into_tree`].
+    pub fn map_panes<NewPane>(
+        self,
+        mut f: impl FnMut(TileId, Pane) -> Option<NewPane>,
+    ) -> TreeV1<NewPane> {
+        let tiles = self
+            .tiles
+            .into_iter()
+            .filter_map(|tile| {
+                Some(match tile {
+                    TileV1::Pane { id, pane } => TileV1::Pane {
+                        id,
+                        pane: f(id, pane)?,
+                    },
+                    TileV1::Tabs {
+                        id,
+                        children,
+                        active,
+                        keep_if_empty,
+                        placeholder,
+                    } => TileV1::Tabs {
+                        id,
+                        children,
+                        active,
+                        keep_if_empty,
+                        placeholder,
+                    },
+                    TileV1::Horizontal {
+                        id,
+                        children,
+                        keep_if_empty,
+                        auto_tiling,
+                    } => TileV1::Horizontal {
+                        id,
+                        children,
+                        keep_if_empty,
+                        auto_tiling,
+                    },
+                    TileV1::Vertical {
+                        id,
+                        children,
+                        keep_if_empty,
+                        auto_tiling,
+                    } => TileV1::Vertical {
+                        id,
+                        children,
+                        keep_if_empty,
+                        auto_tiling,
+                    },
+                    TileV1::Grid {
+                        id,
+                        children,
+                        layout,
+                        col_shares,
+                        row_shares,
+                        keep_if_empty,
+                        auto_tiling,
+                    } => TileV1::Grid {
+                        id,
+                        children,
+                        layout,
+                        col_shares,
+                        row_shares,
+                        keep_if_empty,
+                        auto_tiling,
+                    },
+                    TileV1::Unknown => TileV1::Unknown,
+                })
+            })
+            .collect();
+
+        TreeV1 {
+            root: self.root,
+            tiles,
+        }
+    }
+
+    /// Build a [`Tree`], repairing whatever is broken.
+    ///
+    /// Unknown and duplicate tiles are skipped, and references to missing tiles are removed.
+    pub fn into_tree(self) -> Tree<Pane> {
+        let known: nohash_hasher::IntSet<TileId> =
+            self.tiles.iter().filter_map(TileV1::id).collect();
+        let is_known = |child: &TileId| {
+            let is_known = known.contains(child);
+            if !is_known {
+                log::warn!("Removing reference to missing tile {child:?}");
+            }
+            is_known
+        };
+
+        let mut tiles = Tiles::default();
+        for tile in self.tiles {
+            let Some(id) = tile.id() else {
+                log::warn!("Skipping tile of unknown kind");
+                continue;
+            };
+            if tiles.tiles.contains_key(&id) {
+                log::warn!("Skipping duplicate tile {id:?}");
+                continue;
+            }
+
+            let tile = match tile {
+                TileV1::Pane { pane, .. } => Tile::Pane(pane),
+                TileV1::Tabs {
+                    mut children,
+                    active,
+                    keep_if_empty,
+                    placeholder,
+                    ..
+                } => {
+                    children.retain(is_known);
+                    let active = active
+                        .filter(|active| children.contains(active))
+                        .or_else(|| children.first().copied());
+                    Tile::Container(Container::Tabs(Tabs {
+                        children,
+                        active,
+                        keep_if_empty,
+                        placeholder,
+                    }))
+                }
+                TileV1::Horizontal {
+                    children,
+                    keep_if_empty,
+                    auto_tiling,
+                    ..
+                } => Tile::Container(Container::Linear(linear_from_v1(
+                    LinearDir::Horizontal,
+                    children,
+                    keep_if_empty,
+                    auto_tiling,
+                    is_known,
+                ))),
+                TileV1::Vertical {
+                    children,
+                    keep_if_empty,
+                    auto_tiling,
+                    ..
+                } => Tile::Container(Container::Linear(linear_from_v1(
+                    LinearDir::Vertical,
+                    children,
+                    keep_if_empty,
+                    auto_tiling,
+                    is_known,
+                ))),
+                TileV1::Grid {
+                    children,
+                    layout,
+                    col_shares,
+                    row_shares,
+                    keep_if_empty,
+                    auto_tiling,
+                    ..
+                } => {
+                    let children: Vec<GridChildV1> = children
+                        .into_iter()
+                        .filter(|child| is_known(&child.id))
+                        .collect();
+                    let mut grid = Grid::new(children.iter().map(|child| child.id).collect());
+                    grid.layout = layout;
+                    grid.col_shares = col_shares;
+                    grid.row_shares = row_shares;
+                    grid.keep_if_empty = keep_if_empty;
+                    grid.auto_tiling = auto_tiling;
+                    for child in children {
+                        if let Some(loc) = child.loc {
+                            grid.locations.insert(child.id, loc);
+                        }
+                    }
+                    Tile::Container(Container::Grid(grid))
+                }
+                TileV1::Unknown => continue,
+            };
+            tiles.tiles.insert(id, tile);
+        }
+
+        let root = self.root.filter(|root| {
+            let exists = tiles.tiles.contains_key(root);
+            if !exists {
+                log::warn!("Failed to find root {root:?}");
+            }
+            exists
+        });
+
+        Tree { root, tiles }
+    }
+}
+
+fn linear_from_v1(
+    dir: LinearDir,
+    children: Vec<LinearChildV1>,
+    keep_if_empty: bool,
+    auto_tiling: Option<TilingStrategy>,
+    is_known: impl Fn(&TileId) -> bool,
+) -> Linear {
+    let children: Vec<LinearChildV1> = children
+        .into_iter()
+        .filter(|child| is_known(&child.id))
+        .collect();
+    let mut linear = Linear::new(dir, children.iter().map(|child| child.id).collect());
+    for child in children {
+        linear.shares[child.id] = child.share;
+    }
+    linear.keep_if_empty = keep_if_empty;
+    linear.auto_tiling = auto_tiling;
+    linear
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::{value::Error, IntoDeserializer as _};
+
+    use super::*;
+
+    fn to_json<Pane: Serialize>(tree: &Tree<Pane>) -> serde_json::Value {
+        serde_json::to_value(tree.to_serialized()).expect("serializing to json can't fail")
+    }
+
+    #[test]
+    fn round_trip_latest_version() {
+        let tree = Tree::new_tabs(vec!["a".to_owned(), "b".to_owned()]);
+
+        let json = serde_json::to_string(&tree.to_serialized()).expect("serializable");
+        assert!(json.starts_with(r#"{"version":"1""#));
+
+        let loaded: SerializedTree<String> = serde_json::from_str(&json).expect("loadable");
+        assert_eq!(to_json(&Tree::from_serialized(loaded)), to_json(&tree));
+    }
+
+    #[test]
+    fn round_trip_legacy_version() {
+        let tree = Tree::new_horizontal(vec![1, 2, 3]);
+        let legacy_json = serde_json::to_string(&tree).expect("serializable");
+
+        let loaded: SerializedTree<i32> = serde_json::from_str(&legacy_json).expect("loadable");
+        assert_eq!(loaded.version(), 0);
+        assert_eq!(to_json(&Tree::from_serialized(loaded)), to_json(&tree));
+    }
+
+    #[test]
+    fn round_trip_auto_tiling() {
+        let mut tree = Tree::from_layout_string("h[a, b]", |name| Some(name.to_owned()))
+            .expect("valid layout");
+        let root = tree.root().expect("non-empty tree");
+        tree.set_auto_tiling(root, Some(TilingStrategy::Spiral));
+
+        let json = serde_json::to_string(&tree.to_serialized()).expect("serializable");
+        let loaded: SerializedTree<String> = serde_json::from_str(&json).expect("loadable");
+        let mut loaded = Tree::from_serialized(loaded);
+        assert_eq!(loaded.auto_tiling(root), Some(TilingStrategy::Spiral));
+
+        loaded.auto_retile(); // left as it was loaded
+        assert_eq!(loaded.to_layout_string(Clone::clone), "h[a, b]");
+
+        let c = loaded.tiles.insert_pane("c".to_owned());
+        if let Some(Tile::Container(container)) = loaded.tiles.get_mut(root) {
+            container.add_child(c);
+        }
+        loaded.auto_retile();
+        assert_eq!(loaded.to_layout_string(Clone::clone), "h[a, v[b, c]]");
+    }
+
+    #[test]
+    fn errors_point_to_where_they_are() {
+        let json = r#"{
+            "version": "1",
+            "root": "not an id",
+            "tiles": []
+        }"#;
+        let err = serde_json::from_str::<SerializedTree<String>>(json).expect_err("bad root");
+        assert_eq!(err.line(), 3, "{err}");
+        assert!(err.to_string().contains("not an id"), "{err}");
+
+        let json = r#"{"version": "2", "root": null, "tiles": []}"#;
+        let err = serde_json::from_str::<SerializedTree<String>>(json).expect_err("bad version");
+        assert!(err.to_string().contains("unknown variant `2`"), "{err}");
+
+        let json = r#"{"root": null, "version": "1", "tiles": []}"#;
+        let err = serde_json::from_str::<SerializedTree<String>>(json).expect_err("late version");
+        assert!(err.to_string().contains("must be the first field"), "{err}");
+    }
+
+    #[test]
+    fn tile_id_from_any_integer_or_string() {
+        let expected: Result<TileId, Error> = Ok(TileId::from_u64(42));
+        assert_eq!(TileId::deserialize(42_u64.into_deserializer()), expected);
+        assert_eq!(TileId::deserialize(42_i64.into_deserializer()), expected);
+        assert_eq!(TileId::deserialize("42".into_deserializer()), expected);
+
+        let negative: Result<TileId, Error> = TileId::deserialize((-1_i64).into_deserializer());
+        assert!(negative.is_err());
+    }
+}
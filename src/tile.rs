@@ -1,25 +1,83 @@
 use crate::{Container, ContainerKind};
 
 /// An identifier for a [`Tile`] in the tree, be it a [`Container`] or a pane.
-#[derive(Clone, Copy, Hash, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Hash, PartialEq, Eq, serde::Serialize)]
 pub struct TileId(u64);
 
-/// [`TileId`] is a high-entropy random id, so this is fine:
+/// [`TileId`] is usually a high-entropy random id, and a sequential id hashes fine too:
 impl nohash_hasher::IsEnabled for TileId {}
 
 impl TileId {
     /// Generate a new random [`TileId`].
+    #[cfg(feature = "rand")]
     pub fn random() -> Self {
         use rand::Rng as _;
         Self(rand::thread_rng().gen())
     }
 
+    pub fn from_u64(value: u64) -> Self {
+        Self(value)
+    }
+
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+
     /// Corresponding [`egui::Id`], used for dragging.
     pub fn id(&self) -> egui::Id {
         egui::Id::new(self)
     }
 }
 
+/// Like the derived implementation, but also accepts ids stored as strings or signed integers.
+///
+/// That is how JSON stores map keys, and it is what `serde` sees when it needs to buffer
+/// the input, e.g. for the internally tagged [`crate::SerializedTree`].
+impl<'de> serde::Deserialize<'de> for TileId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct("TileId", TileIdVisitor)
+    }
+}
+
+struct TileIdVisitor;
+
+impl<'de> serde::de::Visitor<'de> for TileIdVisitor {
+    type Value = TileId;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("a tile id")
+    }
+
+    fn visit_newtype_struct<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<TileId, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(self)
+        } else {
+            deserializer.deserialize_u64(self)
+        }
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<TileId, E> {
+        Ok(TileId(value))
+    }
+
+    /// Some formats, e.g. TOML, only have signed integers.
+    fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<TileId, E> {
+        u64::try_from(value)
+            .map(TileId)
+            .map_err(|_err| E::invalid_value(serde::de::Unexpected::Signed(value), &self))
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<TileId, E> {
+        value
+            .parse()
+            .map(TileId)
+            .map_err(|_err| E::invalid_value(serde::de::Unexpected::Str(value), &self))
+    }
+}
+
 impl std::fmt::Debug for TileId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:08X}", self.0 as u32)
@@ -28,6 +86,88 @@ impl std::fmt::Debug for TileId {
 
 // ----------------------------------------------------------------------------
 
+/// How [`crate::Tiles`] come up with the ids of new tiles.
+///
+/// The default is [`Self::Random`], which makes it safe to move tiles between trees,
+/// and to load layouts saved by other runs of your app.
+/// Use [`Self::sequential`] or [`Self::seeded`] to get the same ids each run,
+/// e.g. for snapshot tests or golden files.
+///
+/// Whatever the allocator, [`crate::Tiles`] never hands out an id that is already in use.
+///
+/// ```
+/// use egui_tiles::{IdAllocator, Tiles, TileId};
+///
+/// let mut tiles = Tiles::<()>::default();
+/// tiles.id_allocator = IdAllocator::sequential();
+/// assert_eq!(tiles.insert_pane(()), TileId::from_u64(1));
+/// assert_eq!(tiles.insert_pane(()), TileId::from_u64(2));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum IdAllocator {
+    /// Random ids.
+    ///
+    /// Without the `rand` feature these are derived from [`std::collections::hash_map::RandomState`],
+    /// which avoids `getrandom` on the web, but may repeat between runs there.
+    #[default]
+    Random,
+
+    /// 1, 2, 3, …
+    Sequential { next: u64 },
+
+    /// Pseudo-random ids that are the same every run for the same seed.
+    Seeded { state: u64 },
+}
+
+impl IdAllocator {
+    pub fn sequential() -> Self {
+        Self::Sequential { next: 1 }
+    }
+
+    pub fn seeded(seed: u64) -> Self {
+        Self::Seeded { state: seed }
+    }
+
+    /// The next id. Use [`crate::Tiles::insert_tile`] to also avoid ids that are already in use.
+    pub fn next_id(&mut self) -> TileId {
+        match self {
+            Self::Random => TileId(random_u64()),
+            Self::Sequential { next } => {
+                let id = TileId(*next);
+                *next = next.wrapping_add(1);
+                id
+            }
+            Self::Seeded { state } => {
+                // SplitMix64:
+                *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                let mut z = *state;
+                z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                TileId(z ^ (z >> 31))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+fn random_u64() -> u64 {
+    use rand::Rng as _;
+    rand::thread_rng().gen()
+}
+
+#[cfg(not(feature = "rand"))]
+fn random_u64() -> u64 {
+    use std::hash::{BuildHasher as _, Hasher as _};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish()
+}
+
+// ----------------------------------------------------------------------------
+
 /// A tile in the tree. Either a pane (leaf) or a [`Container`] of more tiles.
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum Tile<Pane> {
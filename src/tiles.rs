@@ -1,8 +1,9 @@
 use egui::{Pos2, Rect};
 
 use super::{
-    Behavior, Container, ContainerInsertion, ContainerKind, GcAction, Grid, InsertionPoint, Linear,
-    LinearDir, SimplificationOptions, SimplifyAction, Tabs, Tile, TileId,
+    keyed::KeyIndex, tiling::TiledLeaves, Behavior, Container, ContainerInsertion, ContainerKind,
+    GcAction, Grid, IdAllocator, InsertionPoint, Linear, LinearDir, SimplificationOptions,
+    SimplifyAction, Tabs, Tile, TileId,
 };
 
 /// Contains all tile state, but no root.
@@ -25,6 +26,32 @@ pub struct Tiles<Pane> {
     /// Filled in by the layout step at the start of each frame.
     #[serde(default, skip)]
     pub(super) rects: nohash_hasher::IntMap<TileId, Rect>,
+
+    /// The tiles shown by the last call to [`crate::Tree::ui`], and where.
+    #[serde(default, skip)]
+    pub(super) shown: nohash_hasher::IntMap<TileId, Rect>,
+
+    /// The [`egui::Context::frame_nr`] of the last call to [`crate::Tree::ui`].
+    #[serde(default, skip)]
+    pub(super) shown_frame_nr: Option<u64>,
+
+    /// The [`egui::Ui::id`] of the last call to [`crate::Tree::ui`], which tells trees apart.
+    #[serde(skip, default = "egui::Id::null")]
+    pub(super) ui_id: egui::Id,
+
+    /// How ids are picked for new tiles.
+    ///
+    /// This is not part of [`crate::SerializedTree`], so it is reset to the default when loading one.
+    #[serde(default)]
+    pub id_allocator: IdAllocator,
+
+    /// Used by [`Self::find_by_key`].
+    #[serde(skip)]
+    pub(super) key_index: KeyIndex,
+
+    /// Used by [`crate::Tree::auto_retile`].
+    #[serde(skip)]
+    pub(super) tiled_leaves: TiledLeaves,
 }
 
 impl<Pane> Default for Tiles<Pane> {
@@ -32,6 +59,12 @@ impl<Pane> Default for Tiles<Pane> {
         Self {
             tiles: Default::default(),
             rects: Default::default(),
+            shown: Default::default(),
+            shown_frame_nr: None,
+            ui_id: egui::Id::null(),
+            id_allocator: Default::default(),
+            key_index: Default::default(),
+            tiled_leaves: Default::default(),
         }
     }
 }
@@ -57,13 +90,35 @@ impl<Pane> Tiles<Pane> {
         self.tiles.get_mut(&tile_id)
     }
 
+    /// An id not used by any tile, nor reserved by the caller.
+    pub(super) fn next_free_id(&mut self, is_reserved: impl Fn(TileId) -> bool) -> TileId {
+        loop {
+            let id = self.id_allocator.next_id();
+            if !self.tiles.contains_key(&id) && !is_reserved(id) {
+                return id;
+            }
+        }
+    }
+
     #[must_use]
     pub fn insert_tile(&mut self, tile: Tile<Pane>) -> TileId {
-        let id = TileId::random();
+        if let Tile::Pane(_) = tile {
+            self.key_index.invalidate();
+        }
+        let id = self.next_free_id(|_| false);
         self.tiles.insert(id, tile);
         id
     }
 
+    /// Re-insert a tile that was taken out of the map under a new id,
+    /// so that its old id can be given to a container wrapping it.
+    fn move_to_new_id(&mut self, old_id: TileId, tile: Tile<Pane>) -> TileId {
+        self.key_index.invalidate(); // In case it's a pane
+        let new_id = self.next_free_id(|id| id == old_id);
+        self.tiles.insert(new_id, tile);
+        new_id
+    }
+
     #[must_use]
     pub fn insert_pane(&mut self, pane: Pane) -> TileId {
         self.insert_tile(Tile::Pane(pane))
@@ -95,6 +150,27 @@ impl<Pane> Tiles<Pane> {
         )))
     }
 
+    /// Insert an empty placeholder with the given label, see [`crate::Tabs::placeholder`].
+    ///
+    /// ```
+    /// use egui_tiles::{SimplificationOptions, Tiles, Tree};
+    ///
+    /// let mut tiles = Tiles::default();
+    /// let map = tiles.insert_pane("map");
+    /// let slot = tiles.insert_placeholder("Drop a chart here");
+    /// let root = tiles.insert_vertical_tile(vec![map, slot]);
+    ///
+    /// let mut tree = Tree::new(root, tiles);
+    /// tree.simplify(&SimplificationOptions::default());
+    /// assert!(tree.tiles.get(slot).is_some());
+    /// ```
+    #[must_use]
+    pub fn insert_placeholder(&mut self, label: impl Into<String>) -> TileId {
+        self.insert_tile(Tile::Container(Container::Tabs(Tabs::new_placeholder(
+            label,
+        ))))
+    }
+
     #[must_use]
     pub fn insert_grid_tile(&mut self, children: Vec<TileId>) -> TileId {
         self.insert_tile(Tile::Container(Container::new_grid(children)))
@@ -134,7 +210,7 @@ impl<Pane> Tiles<Pane> {
                     tabs.set_active(child_id);
                     self.tiles.insert(parent_id, tile);
                 } else {
-                    let new_tile_id = self.insert_tile(tile);
+                    let new_tile_id = self.move_to_new_id(parent_id, tile);
                     let mut tabs = Tabs::new(vec![new_tile_id]);
                     tabs.children.insert(index.min(1), child_id);
                     tabs.set_active(child_id);
@@ -153,7 +229,7 @@ impl<Pane> Tiles<Pane> {
                     children.insert(index, child_id);
                     self.tiles.insert(parent_id, tile);
                 } else {
-                    let new_tile_id = self.insert_tile(tile);
+                    let new_tile_id = self.move_to_new_id(parent_id, tile);
                     let mut linear = Linear::new(LinearDir::Horizontal, vec![new_tile_id]);
                     linear.children.insert(index.min(1), child_id);
                     self.tiles
@@ -171,7 +247,7 @@ impl<Pane> Tiles<Pane> {
                     children.insert(index, child_id);
                     self.tiles.insert(parent_id, tile);
                 } else {
-                    let new_tile_id = self.insert_tile(tile);
+                    let new_tile_id = self.move_to_new_id(parent_id, tile);
                     let mut linear = Linear::new(LinearDir::Vertical, vec![new_tile_id]);
                     linear.children.insert(index.min(1), child_id);
                     self.tiles
@@ -185,7 +261,7 @@ impl<Pane> Tiles<Pane> {
                     grid.children.push(child_id);
                     self.tiles.insert(parent_id, tile);
                 } else {
-                    let new_tile_id = self.insert_tile(tile);
+                    let new_tile_id = self.move_to_new_id(parent_id, tile);
                     let mut grid = Grid::new(vec![new_tile_id, child_id]);
                     grid.locations.insert(child_id, insert_location);
                     self.tiles
@@ -294,13 +370,15 @@ impl<Pane> Tiles<Pane> {
             let kind = container.kind();
             container.simplify_children(|child| self.simplify(options, child, Some(kind)));
 
+            let keep = container.keep_if_empty() || container.is_placeholder();
+
             if kind == ContainerKind::Tabs {
-                if options.prune_empty_tabs && container.is_empty() {
+                if options.prune_empty_tabs && container.is_empty() && !keep {
                     log::debug!("Simplify: removing empty tabs container");
                     return SimplifyAction::Remove;
                 }
 
-                if options.prune_single_child_tabs && container.children().len() == 1 {
+                if options.prune_single_child_tabs && container.children().len() == 1 && !keep {
                     let child_is_pane =
                         matches!(self.get(container.children()[0]), Some(Tile::Pane(_)));
 
@@ -322,7 +400,7 @@ impl<Pane> Tiles<Pane> {
                             if let Some(Tile::Container(Container::Linear(child))) =
                                 &mut self.get_mut(child_id)
                             {
-                                if parent.dir == child.dir {
+                                if parent.dir == child.dir && !child.keep_if_empty {
                                     // absorb the child
                                     log::debug!(
                                         "Simplify: absorbing nested linear container with {} children",
@@ -354,11 +432,12 @@ impl<Pane> Tiles<Pane> {
                     }
                 }
 
-                if options.prune_empty_containers && container.is_empty() {
+                if options.prune_empty_containers && container.is_empty() && !keep {
                     log::debug!("Simplify: removing empty container tile");
                     return SimplifyAction::Remove;
                 }
-                if options.prune_single_child_containers && container.children().len() == 1 {
+                if options.prune_single_child_containers && container.children().len() == 1 && !keep
+                {
                     log::debug!("Simplify: collapsing single-child container tile");
                     return SimplifyAction::Replace(container.children()[0]);
                 }
@@ -380,8 +459,7 @@ impl<Pane> Tiles<Pane> {
                 if !parent_is_tabs {
                     // Add tabs to this pane:
                     log::debug!("Auto-adding Tabs-parent to pane {it:?}");
-                    let new_id = TileId::random();
-                    self.tiles.insert(new_id, tile);
+                    let new_id = self.move_to_new_id(it, tile);
                     self.tiles
                         .insert(it, Tile::Container(Container::new_tabs(vec![new_id])));
                     return;
@@ -0,0 +1,388 @@
+//! Automatic placement of new panes, like in a tiling window manager.
+
+use super::{
+    traversal::children_in_order, Container, ContainerKind, Linear, LinearDir, Tile, TileId, Tree,
+};
+
+/// The tiles each auto-tiled container had when it was last arranged, sorted by id,
+/// so that [`Tree::auto_retile`] can tell when panes were added or removed.
+///
+/// The strategy itself is stored in the container, see [`Container::auto_tiling`].
+#[derive(Clone, Debug, Default)]
+pub(super) struct TiledLeaves(pub nohash_hasher::IntMap<TileId, Vec<TileId>>);
+
+/// A cache does not affect equality.
+impl PartialEq for TiledLeaves {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// Where [`Tree::add_pane_auto`] puts a new pane.
+///
+/// ```
+/// use egui_tiles::{TilingStrategy, Tree};
+///
+/// let layout = |strategy: TilingStrategy| {
+///     let mut tree = Tree::empty();
+///     for name in ["a", "b", "c", "d"] {
+///         tree.add_pane_auto(name.to_owned(), strategy);
+///     }
+///     tree.to_layout_string(|pane| pane.clone())
+/// };
+///
+/// assert_eq!(layout(TilingStrategy::Bsp), "h[v[a, c], v[b, d]]");
+/// assert_eq!(layout(TilingStrategy::Spiral), "h[a, v[b, h[c, d]]]");
+/// assert_eq!(
+///     layout(TilingStrategy::MasterStack { master_fraction: 0.75 }),
+///     "h[0.75:a, 0.25:v[b, c, d]]"
+/// );
+/// assert_eq!(layout(TilingStrategy::Columns), "h[a, b, c, d]");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TilingStrategy {
+    /// Binary space partitioning: split the largest visible pane in two,
+    /// along its longest side.
+    ///
+    /// Before the tree has been shown, the pane closest to the root is split,
+    /// across the direction of its parent.
+    Bsp,
+
+    /// Split the last pane in half, alternating between horizontal and vertical splits,
+    /// so that each pane is half the size of the one before it.
+    Spiral,
+
+    /// One large pane on the left, and all other panes stacked vertically on the right.
+    ///
+    /// If the root is a horizontal split in two, its right half is taken to be the stack.
+    MasterStack {
+        /// How much of the width the master pane gets, between 0 and 1.
+        master_fraction: f32,
+    },
+
+    /// All panes side by side in columns at the root.
+    ///
+    /// A new column is as wide as the average column; the others keep their relative widths.
+    Columns,
+}
+
+impl<Pane> Tree<Pane> {
+    /// Add a pane, and place it according to the given strategy.
+    ///
+    /// Returns the id of the new pane.
+    pub fn add_pane_auto(&mut self, pane: Pane, strategy: TilingStrategy) -> TileId {
+        let tile_id = self.tiles.insert_pane(pane);
+        self.place_tile(tile_id, strategy);
+        tile_id
+    }
+
+    /// Re-arrange the panes in the given container with the given strategy,
+    /// as if they were added one at a time, in the order they are shown.
+    ///
+    /// Tabs and other auto-tiled containers (see [`Self::set_auto_tiling`]) inside it are moved as a whole;
+    /// the other containers inside it are discarded.
+    /// The container itself keeps its id, but may change kind.
+    ///
+    /// ```
+    /// use egui_tiles::{TilingStrategy, Tree};
+    ///
+    /// let mut tree = Tree::from_layout_string("tabs[a, h[b, v[c, tabs[d, e]]]]", |name| Some(name.to_owned())).unwrap();
+    /// let root = tree.root().unwrap();
+    /// tree.retile(root, TilingStrategy::Columns);
+    /// assert_eq!(tree.root(), Some(root));
+    /// assert_eq!(tree.to_layout_string(|pane| pane.clone()), "h[a, b, c, tabs[d, e]]");
+    /// ```
+    pub fn retile(&mut self, container_id: TileId, strategy: TilingStrategy) {
+        let Some(Tile::Container(container)) = self.tiles.get(container_id) else {
+            return; // A pane, or nothing: nothing to re-arrange
+        };
+        let keep_if_empty = container.keep_if_empty();
+        let auto_tiling = container.auto_tiling();
+        let (leaves, inner) = self.tiling_leaves(container_id);
+
+        // Arrange stand-ins for the leaves on the side, then move the new containers in:
+        let mut scratch: Tree<()> = Tree::empty();
+        scratch.tiles.id_allocator = self.tiles.id_allocator;
+        for &leaf in &leaves {
+            scratch.tiles.tiles.insert(leaf, Tile::Pane(()));
+            scratch.place_tile(leaf, strategy);
+        }
+        self.tiles.id_allocator = scratch.tiles.id_allocator;
+
+        for inner in inner {
+            self.tiles.tiles.remove(&inner);
+            self.tiles.rects.remove(&inner);
+        }
+
+        let mut new_container = match scratch.root {
+            Some(root) if matches!(scratch.tiles.get(root), Some(Tile::Container(_))) => {
+                let containers = scratch
+                    .tiles
+                    .tiles
+                    .into_iter()
+                    .filter_map(|(tile_id, tile)| match tile {
+                        Tile::Container(container) => Some((tile_id, Tile::Container(container))),
+                        Tile::Pane(()) => None,
+                    })
+                    .collect();
+                let arranged = self.adopt_tiles(containers, root);
+                match self.tiles.tiles.remove(&arranged) {
+                    Some(Tile::Container(container)) => container,
+                    _ => return, // adopt_tiles always inserts the root
+                }
+            }
+            _ => Container::new_linear(LinearDir::Horizontal, scratch.root.into_iter().collect()),
+        };
+        new_container.set_keep_if_empty(keep_if_empty);
+        new_container.set_auto_tiling(auto_tiling);
+        self.tiles
+            .tiles
+            .insert(container_id, Tile::Container(new_container));
+    }
+
+    /// Keep the panes in the given container arranged with the given strategy,
+    /// re-arranging them with [`Self::retile`] whenever [`Self::ui`] sees that panes were added to or removed from it.
+    ///
+    /// They are re-arranged right away, too, which turns the container into a [`Linear`] one.
+    /// Pass `None` to stop. Moving panes around inside the container is left alone until the next addition or removal.
+    ///
+    /// The container is marked as [`Container::keep_if_empty`], so that simplification doesn't remove it.
+    /// The strategy is stored in the container (see [`Container::auto_tiling`]),
+    /// so it is kept by [`crate::SerializedTree`], but not by [`Self::to_layout_string`].
+    ///
+    /// ```
+    /// use egui_tiles::{TilingStrategy, Tree};
+    ///
+    /// let mut tree = Tree::from_layout_string("h[a, b]", |name| Some(name.to_owned())).unwrap();
+    /// let root = tree.root().unwrap();
+    /// tree.set_auto_tiling(root, Some(TilingStrategy::Spiral));
+    /// assert_eq!(tree.auto_tiling(root), Some(TilingStrategy::Spiral));
+    ///
+    /// let c = tree.tiles.insert_pane("c".to_owned());
+    /// if let Some(egui_tiles::Tile::Container(container)) = tree.tiles.get_mut(root) {
+    ///     container.add_child(c);
+    /// }
+    /// tree.auto_retile(); // `Tree::ui` does this for you
+    /// assert_eq!(tree.to_layout_string(|pane| pane.clone()), "h[a, v[b, c]]");
+    /// ```
+    pub fn set_auto_tiling(&mut self, container_id: TileId, strategy: Option<TilingStrategy>) {
+        let Some(Tile::Container(container)) = self.tiles.get_mut(container_id) else {
+            log::warn!("Failed to auto-tile {container_id:?}: not a container");
+            return;
+        };
+        let Some(strategy) = strategy else {
+            container.set_auto_tiling(None);
+            self.tiles.tiled_leaves.0.remove(&container_id);
+            return;
+        };
+        container.set_keep_if_empty(true);
+        self.retile(container_id, strategy);
+        if let Some(Tile::Container(container)) = self.tiles.get_mut(container_id) {
+            container.set_auto_tiling(Some(strategy));
+        }
+        let leaves = self.sorted_tiling_leaves(container_id);
+        self.tiles.tiled_leaves.0.insert(container_id, leaves);
+    }
+
+    /// The strategy the given container is kept arranged with, see [`Self::set_auto_tiling`].
+    pub fn auto_tiling(&self, container_id: TileId) -> Option<TilingStrategy> {
+        match self.tiles.get(container_id) {
+            Some(Tile::Container(container)) => container.auto_tiling(),
+            _ => None,
+        }
+    }
+
+    /// Re-arrange the auto-tiled containers that had panes added or removed since they were last arranged.
+    ///
+    /// A container seen for the first time, e.g. after loading a tree, is left as it is.
+    ///
+    /// Called by [`Self::ui`], see [`Self::set_auto_tiling`].
+    pub fn auto_retile(&mut self) {
+        let mut auto_tiled: Vec<(TileId, TilingStrategy)> = self
+            .tiles
+            .tiles
+            .iter()
+            .filter_map(|(&tile_id, tile)| match tile {
+                Tile::Container(container) => Some((tile_id, container.auto_tiling()?)),
+                Tile::Pane(_) => None,
+            })
+            .collect();
+        auto_tiled.sort_by_key(|(tile_id, _)| tile_id.as_u64());
+        self.tiles
+            .tiled_leaves
+            .0
+            .retain(|tile_id, _| auto_tiled.iter().any(|(id, _)| id == tile_id));
+
+        for (container_id, strategy) in auto_tiled {
+            let leaves = self.sorted_tiling_leaves(container_id);
+            let changed = matches!(
+                self.tiles.tiled_leaves.0.get(&container_id),
+                Some(previous) if previous != &leaves
+            );
+            if changed {
+                self.retile(container_id, strategy);
+            }
+            self.tiles.tiled_leaves.0.insert(container_id, leaves);
+        }
+    }
+
+    fn sorted_tiling_leaves(&self, container_id: TileId) -> Vec<TileId> {
+        let mut leaves = self.tiling_leaves(container_id).0;
+        leaves.sort_by_key(TileId::as_u64);
+        leaves
+    }
+
+    /// What [`Self::retile`] moves around in the given container, in the order they are shown,
+    /// and the containers in between.
+    fn tiling_leaves(&self, container_id: TileId) -> (Vec<TileId>, Vec<TileId>) {
+        let mut leaves = vec![];
+        let mut inner = vec![];
+        let mut stack = match self.tiles.get(container_id) {
+            Some(Tile::Container(container)) => children_in_order(container),
+            _ => vec![],
+        };
+        stack.reverse();
+        while let Some(it) = stack.pop() {
+            match self.tiles.get(it) {
+                Some(Tile::Container(container))
+                    if container.kind() != ContainerKind::Tabs
+                        && container.auto_tiling().is_none() =>
+                {
+                    inner.push(it);
+                    stack.extend(children_in_order(container).into_iter().rev());
+                }
+                Some(_) => leaves.push(it),
+                None => log::warn!("Failed to find tile {it:?} while retiling {container_id:?}"),
+            }
+        }
+        (leaves, inner)
+    }
+
+    /// Attach a tile that is not yet part of the tree.
+    fn place_tile(&mut self, tile_id: TileId, strategy: TilingStrategy) {
+        let Some(root) = self.root else {
+            self.root = Some(tile_id);
+            return;
+        };
+
+        match strategy {
+            TilingStrategy::Bsp => {
+                if let Some(target) = self.largest_pane() {
+                    let dir = match self.tiles.try_rect(target) {
+                        Some(rect) if rect.width() >= rect.height() => LinearDir::Horizontal,
+                        Some(_) => LinearDir::Vertical,
+                        None => self.split_dir(target),
+                    };
+                    self.split(target, tile_id, dir);
+                } else {
+                    self.add_to_root(root, tile_id);
+                }
+            }
+            TilingStrategy::Spiral => {
+                if let Some((target, _)) = self.panes().last() {
+                    let dir = self.split_dir(target);
+                    self.split(target, tile_id, dir);
+                } else {
+                    self.add_to_root(root, tile_id);
+                }
+            }
+            TilingStrategy::MasterStack { master_fraction } => {
+                let stack = match self.tiles.get(root) {
+                    Some(Tile::Container(Container::Linear(linear)))
+                        if linear.dir == LinearDir::Horizontal && linear.children.len() == 2 =>
+                    {
+                        Some(linear.children[1])
+                    }
+                    _ => None,
+                };
+                match stack.map(|stack| (stack, self.tiles.get_mut(stack))) {
+                    Some((_, Some(Tile::Container(Container::Linear(linear)))))
+                        if linear.dir == LinearDir::Vertical =>
+                    {
+                        linear.add_child(tile_id);
+                    }
+                    Some((stack, _)) => self.split(stack, tile_id, LinearDir::Vertical),
+                    None => {
+                        let stack = self.tiles.insert_vertical_tile(vec![tile_id]);
+                        let master_fraction = master_fraction.clamp(0.0, 1.0);
+                        let mut linear = Linear::new(LinearDir::Horizontal, vec![root, stack]);
+                        linear.shares[root] = master_fraction;
+                        linear.shares[stack] = 1.0 - master_fraction;
+                        self.root = Some(self.tiles.insert_container(linear));
+                    }
+                }
+            }
+            TilingStrategy::Columns => match self.tiles.get_mut(root) {
+                Some(Tile::Container(Container::Linear(linear)))
+                    if linear.dir == LinearDir::Horizontal =>
+                {
+                    // As wide as the average column, leaving the others as the user made them:
+                    let share = if linear.children.is_empty() {
+                        1.0
+                    } else {
+                        let total: f32 = linear.children.iter().map(|&c| linear.shares[c]).sum();
+                        total / linear.children.len() as f32
+                    };
+                    linear.add_child(tile_id);
+                    linear.shares[tile_id] = share;
+                }
+                _ => {
+                    self.root = Some(self.tiles.insert_horizontal_tile(vec![root, tile_id]));
+                }
+            },
+        }
+    }
+
+    /// The visible pane with the largest area last frame,
+    /// or the one closest to the root if the tree hasn't been shown yet.
+    fn largest_pane(&self) -> Option<TileId> {
+        let panes: Vec<TileId> = self.visible_panes().map(|(tile_id, _)| tile_id).collect();
+        let area = |tile_id: TileId| self.tiles.try_rect(tile_id).map(|rect| rect.area());
+        if panes.iter().all(|&tile_id| area(tile_id).is_some()) {
+            // `max_by` picks the last of equals, but we want the first:
+            panes.into_iter().rev().max_by(|&a, &b| {
+                let (a, b) = (area(a).unwrap_or_default(), area(b).unwrap_or_default());
+                a.total_cmp(&b)
+            })
+        } else {
+            panes
+                .into_iter()
+                .min_by_key(|&tile_id| self.depth(tile_id).unwrap_or(usize::MAX))
+        }
+    }
+
+    /// Across the direction of the parent of the given tile.
+    fn split_dir(&self, tile_id: TileId) -> LinearDir {
+        match self
+            .tiles
+            .parent_of(tile_id)
+            .and_then(|id| self.tiles.get(id))
+        {
+            Some(Tile::Container(Container::Linear(linear)))
+                if linear.dir == LinearDir::Horizontal =>
+            {
+                LinearDir::Vertical
+            }
+            _ => LinearDir::Horizontal,
+        }
+    }
+
+    /// Replace `target` with a split of `target` and `new`, in that order.
+    fn split(&mut self, target: TileId, new: TileId, dir: LinearDir) {
+        let parent_id = self.tiles.parent_of(target);
+        let split = self
+            .tiles
+            .insert_container(Linear::new(dir, vec![target, new]));
+
+        self.replace_child(parent_id, target, split);
+    }
+
+    /// For when there are no panes to split, e.g. when the root is an empty container.
+    fn add_to_root(&mut self, root: TileId, tile_id: TileId) {
+        if let Some(Tile::Container(container)) = self.tiles.get_mut(root) {
+            container.add_child(tile_id);
+        } else {
+            log::warn!("Failed to place {tile_id:?}: no panes and no root container");
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Drag-and-drop of tiles between different [`Tree`]s.
+//!
+//! The tree that owns the dragged tile publishes a [`DragSession`] in egui memory each frame.
+//! Trees are told apart by the [`egui::Ui::id`] they are shown with.
+//! Other trees pick it up, show their drop zones, and on release store a [`PendingTransfer`].
+//! The actual move happens in [`transfer_between_trees`], which needs access to both trees.
+
+use egui::{Context, Id};
+
+use super::{InsertionPoint, TileId, Tree};
+
+/// A tile being dragged, shared by all trees in the same [`egui::Context`].
+#[derive(Clone, Copy, Debug)]
+pub(super) struct DragSession {
+    /// The [`egui::Ui::id`] of the tree owning the tile.
+    tree_id: Id,
+
+    tile_id: TileId,
+
+    /// When was this last published?
+    frame_nr: u64,
+}
+
+impl DragSession {
+    fn id() -> Id {
+        Id::new("egui_tiles_drag_session")
+    }
+
+    /// Called each frame by the tree that owns the dragged tile.
+    pub(super) fn publish(ctx: &Context, tree_id: Id, tile_id: TileId) {
+        let session = Self {
+            tree_id,
+            tile_id,
+            frame_nr: ctx.frame_nr(),
+        };
+        ctx.data_mut(|data| data.insert_temp(Self::id(), session));
+    }
+
+    /// Is a tile from some other tree being dragged?
+    ///
+    /// The owning tree may be shown before or after us, so we accept a session from last frame too.
+    pub(super) fn foreign(ctx: &Context, tree_id: Id) -> Option<Self> {
+        if !super::is_possible_drag(ctx) {
+            return None;
+        }
+        let session: Self = ctx.data_mut(|data| data.get_temp(Self::id()))?;
+        let is_recent = ctx.frame_nr() <= session.frame_nr + 1;
+        (is_recent && session.tree_id != tree_id).then_some(session)
+    }
+
+    pub(super) fn tile_id(&self) -> TileId {
+        self.tile_id
+    }
+}
+
+/// A tile dropped into a tree other than the one owning it.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct PendingTransfer {
+    source: Id,
+    target: Id,
+    tile_id: TileId,
+    insertion_point: InsertionPoint,
+}
+
+impl PendingTransfer {
+    fn id() -> Id {
+        Id::new("egui_tiles_pending_transfer")
+    }
+
+    /// Called by the receiving tree when the foreign tile is released over it.
+    pub(super) fn store(
+        ctx: &Context,
+        session: DragSession,
+        target: Id,
+        insertion_point: InsertionPoint,
+    ) {
+        let transfer = Self {
+            source: session.tree_id,
+            target,
+            tile_id: session.tile_id,
+            insertion_point,
+        };
+        ctx.data_mut(|data| data.insert_temp(Self::id(), transfer));
+    }
+
+    fn take(ctx: &Context) -> Option<Self> {
+        ctx.data_mut(|data| {
+            let transfer = data.get_temp(Self::id());
+            data.remove::<Self>(Self::id());
+            transfer
+        })
+    }
+}
+
+/// A tile (and all its children) that was moved from one [`Tree`] to another.
+///
+/// Returned by [`transfer_between_trees`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreeTransfer {
+    /// Index of the tree the tile was taken from.
+    pub source: usize,
+
+    /// Index of the tree the tile was dropped into.
+    pub target: usize,
+
+    /// The id of the moved tile in the target tree.
+    ///
+    /// This is usually the same as it was in the source tree,
+    /// but tiles are given new ids if they would collide with tiles in the target.
+    pub tile_id: TileId,
+}
+
+/// Complete a drag-and-drop between two trees.
+///
+/// Call this once per frame, after showing all the given trees.
+/// If a tile from one of the trees was dropped into another one,
+/// it is moved over together with all its children and panes.
+///
+/// Each tree must be shown with its own [`egui::Ui::id`], see [`Tree::ui`].
+/// egui keeps track of a dragged tile by its [`TileId`], so the trees must not have any ids in common either:
+/// keep the default [`crate::IdAllocator::Random`] for them, rather than a sequential or seeded one.
+///
+/// ``` no_run
+/// # struct Pane;
+/// # let ctx = egui::Context::default();
+/// # let mut main: egui_tiles::Tree<Pane> = egui_tiles::Tree::empty();
+/// # let mut dock: egui_tiles::Tree<Pane> = egui_tiles::Tree::empty();
+/// // … show `main` and `dock` …
+/// if let Some(transfer) = egui_tiles::transfer_between_trees(&ctx, &mut [&mut main, &mut dock]) {
+///     println!("Moved {:?} from tree {} to tree {}", transfer.tile_id, transfer.source, transfer.target);
+/// }
+/// ```
+pub fn transfer_between_trees<Pane>(
+    ctx: &Context,
+    trees: &mut [&mut Tree<Pane>],
+) -> Option<TreeTransfer> {
+    let PendingTransfer {
+        source,
+        target,
+        tile_id,
+        insertion_point,
+    } = PendingTransfer::take(ctx)?;
+
+    let Some(source) = trees.iter().position(|tree| tree.tiles.ui_id == source) else {
+        log::warn!("Failed to find the tree owning the dropped tile {tile_id:?}");
+        return None;
+    };
+    let Some(target) = trees.iter().position(|tree| tree.tiles.ui_id == target) else {
+        log::warn!("Failed to find the tree to drop {tile_id:?} into");
+        return None;
+    };
+    if source == target {
+        log::warn!("Tile {tile_id:?} was dropped into a tree shown with the same Ui id");
+        return None;
+    }
+    if trees[source].tiles.get(tile_id).is_none() {
+        log::warn!("Failed to find the dropped tile {tile_id:?}");
+        return None;
+    }
+
+    let subtree = trees[source].extract_subtree(tile_id);
+    let tile_id = trees[target].graft(subtree, insertion_point)?;
+
+    Some(TreeTransfer {
+        source,
+        target,
+        tile_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContainerInsertion;
+
+    #[test]
+    fn trees_with_the_same_tile_ids_are_told_apart() {
+        let mut left = Tree::new_tabs(vec!["a", "b"]);
+        let mut right = left.clone();
+        left.tiles.ui_id = Id::new("left");
+        right.tiles.ui_id = Id::new("right");
+        let left_root = left.root().expect("the tree has a root");
+        let (b, _) = right
+            .panes()
+            .find(|(_, pane)| **pane == "b")
+            .expect("b is in the tree");
+
+        let ctx = Context::default();
+        let session = DragSession {
+            tree_id: right.tiles.ui_id,
+            tile_id: b,
+            frame_nr: ctx.frame_nr(),
+        };
+        let insertion_point = InsertionPoint::new(left_root, ContainerInsertion::Tabs(2));
+        PendingTransfer::store(&ctx, session, left.tiles.ui_id, insertion_point);
+
+        let transfer = transfer_between_trees(&ctx, &mut [&mut left, &mut right]);
+        assert!(matches!(
+            transfer,
+            Some(TreeTransfer {
+                source: 1,
+                target: 0,
+                ..
+            })
+        ));
+        assert_eq!(left.panes().count(), 3);
+        assert_eq!(right.panes().count(), 1);
+    }
+}
@@ -0,0 +1,338 @@
+//! Iterating over and querying the tiles of a [`Tree`].
+
+use std::collections::VecDeque;
+
+use super::{Container, Tile, TileId, Tree};
+
+/// Visits the tiles of a [`Tree`] in depth-first order, see [`Tree::visit`].
+pub trait Visitor<Pane> {
+    fn visit_pane(&mut self, _tile_id: TileId, _pane: &Pane) {}
+
+    /// Return `false` to skip the children of this container.
+    fn enter_container(&mut self, _tile_id: TileId, _container: &Container) -> bool {
+        true
+    }
+
+    /// Called after all the children of the container have been visited.
+    fn leave_container(&mut self, _tile_id: TileId, _container: &Container) {}
+}
+
+/// Like [`Visitor`], but with mutable access to the tiles. See [`Tree::visit_mut`].
+///
+/// The visited tile is temporarily removed from the tree,
+/// so don't look for it (or its ancestors) in the tree while visiting it.
+///
+/// ```
+/// use egui_tiles::{TileId, Tree, VisitorMut};
+///
+/// struct Shout;
+///
+/// impl VisitorMut<String> for Shout {
+///     fn visit_pane(&mut self, _tile_id: TileId, pane: &mut String) {
+///         *pane = pane.to_uppercase();
+///     }
+/// }
+///
+/// let mut tree = Tree::from_layout_string("h[a, v[b, c]]", |name| Some(name.to_owned())).unwrap();
+/// tree.visit_mut(&mut Shout);
+/// assert_eq!(tree.to_layout_string(|pane| pane.clone()), "h[A, v[B, C]]");
+/// ```
+pub trait VisitorMut<Pane> {
+    fn visit_pane(&mut self, _tile_id: TileId, _pane: &mut Pane) {}
+
+    /// Return `false` to skip the children of this container.
+    ///
+    /// Changes made to the children here decide what is visited next.
+    fn enter_container(&mut self, _tile_id: TileId, _container: &mut Container) -> bool {
+        true
+    }
+
+    /// Called after all the children of the container have been visited.
+    fn leave_container(&mut self, _tile_id: TileId, _container: &mut Container) {}
+}
+
+impl<Pane> Tree<Pane> {
+    /// All tiles reachable from the root, parents before children.
+    pub fn iter_depth_first(&self) -> impl Iterator<Item = (TileId, &Tile<Pane>)> + '_ {
+        self.iter_depth_first_where(children_in_order)
+    }
+
+    /// All tiles reachable from the root, one level of the hierarchy at a time.
+    pub fn iter_breadth_first(&self) -> impl Iterator<Item = (TileId, &Tile<Pane>)> + '_ {
+        let mut queue: VecDeque<TileId> = self.root.into_iter().collect();
+        let mut visited = nohash_hasher::IntSet::default();
+        std::iter::from_fn(move || loop {
+            let tile_id = queue.pop_front()?;
+            if !visited.insert(tile_id) {
+                log::warn!("Tile {tile_id:?} was reached twice; the tree is malformed");
+                continue;
+            }
+            let Some(tile) = self.tiles.get(tile_id) else {
+                log::warn!("Missing tile {tile_id:?}");
+                continue;
+            };
+            if let Tile::Container(container) = tile {
+                queue.extend(children_in_order(container));
+            }
+            return Some((tile_id, tile));
+        })
+    }
+
+    /// All panes reachable from the root, in the order they are shown.
+    ///
+    /// All the traversal methods start at the root and visit children in the order they are shown
+    /// (left-to-right, top-to-bottom, first tab to last tab).
+    /// Grid cells are visited row by row, and grid children that have not been placed yet come last.
+    /// Tiles that are not reachable from the root are never visited,
+    /// and neither is a tile reached a second time, should the tree be malformed.
+    ///
+    /// ```
+    /// use egui_tiles::Tree;
+    ///
+    /// let tree = Tree::from_layout_string("h[a, tabs[b, *c]]", |name| Some(name.to_owned())).unwrap();
+    ///
+    /// let panes: Vec<&String> = tree.panes().map(|(_, pane)| pane).collect();
+    /// assert_eq!(panes, ["a", "b", "c"]);
+    ///
+    /// let visible: Vec<&String> = tree.visible_panes().map(|(_, pane)| pane).collect();
+    /// assert_eq!(visible, ["a", "c"]);
+    ///
+    /// let c = tree.find(|_, tile| matches!(tile, egui_tiles::Tile::Pane(pane) if pane == "c")).unwrap();
+    /// assert_eq!(tree.depth(c), Some(2));
+    /// assert_eq!(tree.containing_tabs(c), tree.ancestors(c).first().copied());
+    /// assert_eq!(tree.path_to(c).unwrap().first().copied(), tree.root());
+    /// ```
+    pub fn panes(&self) -> impl Iterator<Item = (TileId, &Pane)> + '_ {
+        self.iter_depth_first().filter_map(only_panes)
+    }
+
+    /// All tiles that are currently shown, i.e. skipping the inactive tabs.
+    pub fn iter_visible(&self) -> impl Iterator<Item = (TileId, &Tile<Pane>)> + '_ {
+        self.iter_depth_first_where(|container| match container {
+            Container::Tabs(tabs) => tabs.active.into_iter().collect(),
+            Container::Linear(_) | Container::Grid(_) => children_in_order(container),
+        })
+    }
+
+    /// All panes that are currently shown, i.e. skipping the inactive tabs.
+    pub fn visible_panes(&self) -> impl Iterator<Item = (TileId, &Pane)> + '_ {
+        self.iter_visible().filter_map(only_panes)
+    }
+
+    /// Depth-first, with `children` picking which children of a container to visit.
+    fn iter_depth_first_where(
+        &self,
+        children: impl Fn(&Container) -> Vec<TileId> + 'static,
+    ) -> impl Iterator<Item = (TileId, &Tile<Pane>)> + '_ {
+        let mut stack: Vec<TileId> = self.root.into_iter().collect();
+        let mut visited = nohash_hasher::IntSet::default();
+        std::iter::from_fn(move || loop {
+            let tile_id = stack.pop()?;
+            if !visited.insert(tile_id) {
+                log::warn!("Tile {tile_id:?} was reached twice; the tree is malformed");
+                continue;
+            }
+            let Some(tile) = self.tiles.get(tile_id) else {
+                log::warn!("Missing tile {tile_id:?}");
+                continue;
+            };
+            if let Tile::Container(container) = tile {
+                stack.extend(children(container).into_iter().rev());
+            }
+            return Some((tile_id, tile));
+        })
+    }
+
+    /// The first tile, in depth-first order, matching the predicate.
+    pub fn find(&self, mut predicate: impl FnMut(TileId, &Tile<Pane>) -> bool) -> Option<TileId> {
+        self.iter_depth_first()
+            .find(|&(tile_id, tile)| predicate(tile_id, tile))
+            .map(|(tile_id, _)| tile_id)
+    }
+
+    /// The root, then the child of the root leading to the given tile, and so on,
+    /// ending with the given tile itself.
+    ///
+    /// `None` if the tile can't be reached from the root.
+    pub fn path_to(&self, tile_id: TileId) -> Option<Vec<TileId>> {
+        fn search<Pane>(
+            tree: &Tree<Pane>,
+            path: &mut Vec<TileId>,
+            visited: &mut nohash_hasher::IntSet<TileId>,
+            it: TileId,
+            target: TileId,
+        ) -> bool {
+            if !visited.insert(it) {
+                return false;
+            }
+            path.push(it);
+            if it == target {
+                return true;
+            }
+            if let Some(Tile::Container(container)) = tree.tiles.get(it) {
+                for child in children_in_order(container) {
+                    if search(tree, path, visited, child, target) {
+                        return true;
+                    }
+                }
+            }
+            path.pop();
+            false
+        }
+
+        let mut path = vec![];
+        let mut visited = nohash_hasher::IntSet::default();
+        search(self, &mut path, &mut visited, self.root?, tile_id).then_some(path)
+    }
+
+    /// The parent of the given tile, then its parent, and so on up to and including the root.
+    ///
+    /// Empty for the root, and for tiles that can't be reached from the root.
+    pub fn ancestors(&self, tile_id: TileId) -> Vec<TileId> {
+        let mut path = self.path_to(tile_id).unwrap_or_default();
+        path.pop();
+        path.reverse();
+        path
+    }
+
+    /// How many ancestors the given tile has, i.e. zero for the root.
+    ///
+    /// `None` if the tile can't be reached from the root.
+    pub fn depth(&self, tile_id: TileId) -> Option<usize> {
+        self.path_to(tile_id).map(|path| path.len() - 1)
+    }
+
+    /// The closest ancestor of the given tile that is a [`Container::Tabs`].
+    pub fn containing_tabs(&self, tile_id: TileId) -> Option<TileId> {
+        self.ancestors(tile_id).into_iter().find(|&ancestor| {
+            matches!(
+                self.tiles.get(ancestor),
+                Some(Tile::Container(Container::Tabs(_)))
+            )
+        })
+    }
+
+    /// Visit all tiles reachable from the root, in depth-first order.
+    pub fn visit(&self, visitor: &mut dyn Visitor<Pane>) {
+        fn visit<Pane>(
+            tree: &Tree<Pane>,
+            visitor: &mut dyn Visitor<Pane>,
+            visited: &mut nohash_hasher::IntSet<TileId>,
+            tile_id: TileId,
+        ) {
+            if !visited.insert(tile_id) {
+                log::warn!("Tile {tile_id:?} was reached twice; the tree is malformed");
+                return;
+            }
+            match tree.tiles.get(tile_id) {
+                Some(Tile::Pane(pane)) => visitor.visit_pane(tile_id, pane),
+                Some(Tile::Container(container)) => {
+                    if visitor.enter_container(tile_id, container) {
+                        for child in children_in_order(container) {
+                            visit(tree, visitor, visited, child);
+                        }
+                    }
+                    visitor.leave_container(tile_id, container);
+                }
+                None => log::warn!("Missing tile {tile_id:?}"),
+            }
+        }
+
+        if let Some(root) = self.root {
+            visit(self, visitor, &mut Default::default(), root);
+        }
+    }
+
+    /// Visit all tiles reachable from the root, in depth-first order, with mutable access.
+    pub fn visit_mut(&mut self, visitor: &mut dyn VisitorMut<Pane>) {
+        fn visit<Pane>(
+            tree: &mut Tree<Pane>,
+            visitor: &mut dyn VisitorMut<Pane>,
+            visited: &mut nohash_hasher::IntSet<TileId>,
+            tile_id: TileId,
+        ) {
+            if !visited.insert(tile_id) {
+                log::warn!("Tile {tile_id:?} was reached twice; the tree is malformed");
+                return;
+            }
+            let Some(mut tile) = tree.tiles.tiles.remove(&tile_id) else {
+                log::warn!("Missing tile {tile_id:?}");
+                return;
+            };
+            match &mut tile {
+                Tile::Pane(pane) => visitor.visit_pane(tile_id, pane),
+                Tile::Container(container) => {
+                    if visitor.enter_container(tile_id, container) {
+                        for child in children_in_order(container) {
+                            visit(tree, visitor, visited, child);
+                        }
+                    }
+                    visitor.leave_container(tile_id, container);
+                }
+            }
+            tree.tiles.tiles.insert(tile_id, tile);
+        }
+
+        if let Some(root) = self.root {
+            visit(self, visitor, &mut Default::default(), root);
+        }
+    }
+}
+
+/// The children of a container in the order they are shown:
+/// grid children row by row, with the ones that have not been placed yet last.
+pub(super) fn children_in_order(container: &Container) -> Vec<TileId> {
+    let mut children = container.children().to_vec();
+    if let Container::Grid(grid) = container {
+        // Stable, so unplaced children keep their order:
+        children.sort_by_key(|child| {
+            let loc = grid.locations.get(child).copied();
+            (loc.is_none(), loc)
+        });
+    }
+    children
+}
+
+fn only_panes<Pane>((tile_id, tile): (TileId, &Tile<Pane>)) -> Option<(TileId, &Pane)> {
+    match tile {
+        Tile::Pane(pane) => Some((tile_id, pane)),
+        Tile::Container(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GridLoc, Tiles};
+
+    #[test]
+    fn traversals_end_on_a_cycle() {
+        let mut tiles = Tiles::default();
+        let a = tiles.insert_pane("a");
+        let inner = tiles.insert_vertical_tile(vec![a]);
+        let root = tiles.insert_horizontal_tile(vec![inner]);
+        if let Some(Tile::Container(container)) = tiles.get_mut(inner) {
+            container.add_child(root);
+        }
+        let tree = Tree::new(root, tiles);
+
+        assert_eq!(tree.iter_depth_first().count(), 3);
+        assert_eq!(tree.iter_breadth_first().count(), 3);
+        assert_eq!(tree.depth(a), Some(2));
+    }
+
+    #[test]
+    fn grid_children_are_visited_row_by_row() {
+        let mut tree = Tree::new_grid(vec!["a", "b", "c"]);
+        let root = tree.root().expect("the tree has a root");
+        let Some(Tile::Container(Container::Grid(grid))) = tree.tiles.get_mut(root) else {
+            panic!("root should be a grid");
+        };
+        let (a, b) = (grid.children[0], grid.children[1]);
+        grid.locations.insert(a, GridLoc::from_col_row(0, 1));
+        grid.locations.insert(b, GridLoc::from_col_row(1, 0));
+
+        let panes: Vec<&str> = tree.panes().map(|(_, &pane)| pane).collect();
+        assert_eq!(panes, ["b", "a", "c"]);
+    }
+}
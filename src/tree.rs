@@ -1,10 +1,14 @@
 use egui::{NumExt as _, Rect, Ui};
 
-use crate::{ContainerKind, UiResponse};
+use crate::{ContainerKind, DropTargetMode, UiResponse};
 
 use super::{
-    is_possible_drag, Behavior, Container, DropContext, InsertionPoint, SimplificationOptions,
-    SimplifyAction, Tile, TileId, Tiles,
+    compass::{self, CompassButton},
+    dsl, is_possible_drag,
+    transfer::{DragSession, PendingTransfer},
+    Behavior, Container, ContainerInsertion, DragPayload, DropContext, InsertionPoint, ParseError,
+    SerializedTree, SimplificationMode, SimplificationOptions, SimplifyAction, Tile, TileId, Tiles,
+    TreeV1,
 };
 
 /// The top level type. Contains all persistent state, including layouts and sizes.
@@ -135,6 +139,139 @@ impl<Pane> Tree<Pane> {
         Self::new(root, tiles)
     }
 
+    /// Convert to the latest [`SerializedTree`] format, for saving.
+    ///
+    /// Prefer this over serializing the [`Tree`] directly,
+    /// since that format changes whenever the internals of the tree do.
+    pub fn to_serialized(&self) -> SerializedTree<&Pane> {
+        SerializedTree::V1(TreeV1::from_tree(self))
+    }
+
+    /// Load a tree saved in any version of the [`SerializedTree`] format.
+    ///
+    /// Older versions are migrated, and invalid state (e.g. references to missing tiles) is repaired.
+    pub fn from_serialized(serialized: SerializedTree<Pane>) -> Self {
+        serialized.into_latest().into_tree()
+    }
+
+    /// Save only the layout, with each pane replaced by a key of your choice.
+    ///
+    /// Use this to persist the layout separately from the pane data.
+    /// Load it again with [`Self::from_layout`].
+    ///
+    /// ```
+    /// use egui_tiles::Tree;
+    ///
+    /// struct Pane {
+    ///     name: String,
+    ///     cache: Vec<u8>,
+    /// }
+    ///
+    /// let pane = |name: &str| Pane { name: name.to_owned(), cache: vec![0; 1024] };
+    /// let tree = Tree::new_horizontal(vec![pane("a"), pane("b"), pane("c")]);
+    ///
+    /// let json = serde_json::to_string(&tree.to_layout(|pane| pane.name.clone())).unwrap();
+    ///
+    /// // Pane "b" is no longer around:
+    /// let tree = Tree::from_layout(serde_json::from_str(&json).unwrap(), |name: String| {
+    ///     (name != "b").then(|| pane(&name))
+    /// });
+    /// assert_eq!(tree.tiles.tiles.values().filter(|tile| tile.is_pane()).count(), 2);
+    /// ```
+    pub fn to_layout<Key>(&self, mut pane_key: impl FnMut(&Pane) -> Key) -> SerializedTree<Key> {
+        SerializedTree::V1(TreeV1::from_tree(self).map_panes(|_, pane| Some(pane_key(pane))))
+    }
+
+    /// Re-create a tree from a layout saved with [`Self::to_layout`].
+    ///
+    /// Panes for which `resolve` returns `None` are dropped,
+    /// and any containers left empty are removed.
+    pub fn from_layout<Key>(
+        layout: SerializedTree<Key>,
+        mut resolve: impl FnMut(Key) -> Option<Pane>,
+    ) -> Self {
+        let mut tree = layout
+            .into_latest()
+            .map_panes(|_, key| resolve(key))
+            .into_tree();
+        tree.simplify(&SimplificationOptions {
+            prune_empty_tabs: true,
+            prune_empty_containers: true,
+            prune_single_child_tabs: false,
+            prune_single_child_containers: false,
+            all_panes_must_have_tabs: false,
+            join_nested_linear_containerss: false,
+        });
+        tree
+    }
+
+    /// Parse a layout from a compact textual description, e.g. from a config file.
+    ///
+    /// ```text
+    /// h[ tabs[a, *b] 2:v[c, d] grid(2)[e f g] ]
+    /// ```
+    ///
+    /// * `h[…]` and `v[…]` are horizontal and vertical containers.
+    ///   Their children can be prefixed with a share, e.g. `2:` to get twice the default space.
+    /// * `tabs[…]` is a tab container. Prefix the active tab with `*` (defaults to the first one).
+    /// * `grid[…]` is a grid with automatic layout, and `grid(3)[…]` one with three columns.
+    /// * Anything else is the name of a pane, e.g. `a`, `my_pane.2` or `"Name with spaces"`.
+    ///
+    /// Children are separated by commas and/or whitespace.
+    ///
+    /// `make_pane` is called with each pane name.
+    ///
+    /// # Errors
+    /// Returns the line and column of the first syntax error,
+    /// or of the first pane for which `make_pane` returns `None`.
+    ///
+    /// ```
+    /// use egui_tiles::Tree;
+    ///
+    /// let tree = Tree::from_layout_string("h[ tabs[a, *b] 2:v[c, d] ]", |name| {
+    ///     Some(name.to_owned())
+    /// })
+    /// .unwrap();
+    /// assert_eq!(
+    ///     tree.to_layout_string(|name| name.clone()),
+    ///     "h[tabs[a, *b], 2:v[c, d]]"
+    /// );
+    ///
+    /// let err = Tree::from_layout_string("h[a,\n  x[b]]", |name| Some(name.to_owned()))
+    ///     .unwrap_err();
+    /// assert_eq!((err.line, err.column), (2, 3));
+    /// ```
+    pub fn from_layout_string(
+        text: &str,
+        make_pane: impl FnMut(&str) -> Option<Pane>,
+    ) -> Result<Self, ParseError> {
+        dsl::Parser::parse(text, make_pane)
+    }
+
+    /// Describe the layout in the language of [`Self::from_layout_string`],
+    /// naming each pane with `pane_name`.
+    pub fn to_layout_string(&self, mut pane_name: impl FnMut(&Pane) -> String) -> String {
+        let mut out = String::new();
+        if let Some(root) = self.root {
+            dsl::write_tile(&mut out, &self.tiles, root, &mut |_, pane| pane_name(pane));
+        }
+        out
+    }
+
+    /// Like [`Self::to_layout_string`], but names each pane with [`Behavior::tab_title_for_tile`].
+    pub fn to_layout_string_with_titles(&self, behavior: &mut dyn Behavior<Pane>) -> String {
+        let mut out = String::new();
+        if let Some(root) = self.root {
+            dsl::write_tile(&mut out, &self.tiles, root, &mut |tile_id, _| {
+                behavior
+                    .tab_title_for_tile(&self.tiles, tile_id)
+                    .text()
+                    .to_owned()
+            });
+        }
+        out
+    }
+
     pub fn root(&self) -> Option<TileId> {
         self.root
     }
@@ -143,48 +280,268 @@ impl<Pane> Tree<Pane> {
         self.root == Some(tile)
     }
 
+    /// Was the given tile shown this frame or the one before?
+    ///
+    /// Unlike [`Self::iter_visible`], this takes into account whether the tree itself was shown:
+    /// if [`Self::ui`] was not called last frame nor this one, nothing in it is visible.
+    pub fn is_visible(&self, ctx: &egui::Context, tile_id: TileId) -> bool {
+        self.was_shown_recently(ctx) && self.tiles.shown.contains_key(&tile_id)
+    }
+
+    /// The panes shown this frame or the one before, and where they were shown.
+    ///
+    /// Empty if [`Self::ui`] was not called last frame nor this one.
+    pub fn shown_panes(&self, ctx: &egui::Context) -> impl Iterator<Item = (TileId, Rect)> + '_ {
+        let shown = if self.was_shown_recently(ctx) {
+            Some(&self.tiles.shown)
+        } else {
+            None
+        };
+        shown
+            .into_iter()
+            .flatten()
+            .filter(|(tile_id, _)| matches!(self.tiles.get(**tile_id), Some(Tile::Pane(_))))
+            .map(|(&tile_id, &rect)| (tile_id, rect))
+    }
+
+    /// Was [`Self::ui`] called this frame or the one before?
+    fn was_shown_recently(&self, ctx: &egui::Context) -> bool {
+        matches!(self.tiles.shown_frame_nr, Some(last) if last + 1 >= ctx.frame_nr())
+    }
+
     /// Show the tree in the given [`Ui`].
     ///
     /// The tree will use upp all the available space - nothing more, nothing less.
+    ///
+    /// If you show several trees, give each its own [`Ui::id`], e.g. with [`Ui::push_id`].
     pub fn ui(&mut self, behavior: &mut dyn Behavior<Pane>, ui: &mut Ui) {
-        let options = behavior.simplification_options();
-        self.simplify(&options);
-        if options.all_panes_must_have_tabs {
-            if let Some(root) = self.root {
-                self.tiles.make_all_panes_children_of_tabs(false, root);
+        self.tiles.ui_id = ui.id();
+
+        match behavior.simplification_mode() {
+            SimplificationMode::EveryFrame => {
+                self.simplify(&behavior.simplification_options());
             }
+            SimplificationMode::AfterEdits => {
+                let data_id = ui.id().with("simplified_structure");
+                let structure_hash = self.structure_hash();
+                if ui.data_mut(|data| data.get_temp::<u64>(data_id)) != Some(structure_hash) {
+                    self.simplify(&behavior.simplification_options());
+                    let structure_hash = self.structure_hash();
+                    ui.data_mut(|data| data.insert_temp(data_id, structure_hash));
+                }
+            }
+            SimplificationMode::Manual => {}
         }
 
-        self.tiles.gc_root(behavior, self.root);
+        self.gc(behavior);
+
+        self.auto_retile();
 
         self.tiles.rects.clear();
 
-        // Check if anything is being dragged:
+        // Check if anything is being dragged, in this tree or in another one:
+        let mut dragged_tile_id = self.dragged_id(ui.ctx());
+        if let Some(tile_id) = dragged_tile_id {
+            if self.can_drag(behavior, tile_id) {
+                DragSession::publish(ui.ctx(), self.tiles.ui_id, tile_id);
+            } else {
+                ui.memory_mut(|mem| mem.stop_dragging());
+                dragged_tile_id = None;
+            }
+        }
+        let foreign_drag = if dragged_tile_id.is_none() {
+            DragSession::foreign(ui.ctx(), self.tiles.ui_id)
+        } else {
+            None
+        };
+        let dragged_tile_id = dragged_tile_id.or(foreign_drag.map(|session| session.tile_id()));
+        let payload = if dragged_tile_id.is_none() {
+            DragPayload::dragged(ui.ctx())
+        } else {
+            None
+        };
+        let dragging_files = dragged_tile_id.is_none()
+            && payload.is_none()
+            && ui.input(|i| !i.raw.hovered_files.is_empty() || !i.raw.dropped_files.is_empty());
+
+        // Only offer drop zones when the mouse is over this tree, since there may be others.
+        let tree_rect = ui.available_rect_before_wrap();
+        let mouse_pos = ui.input(|i| i.pointer.hover_pos());
+        let is_mouse_over_tree = matches!(mouse_pos, Some(pos) if tree_rect.contains(pos));
+
         let mut drop_context = DropContext {
-            enabled: true,
-            dragged_tile_id: self.dragged_id(ui.ctx()),
-            mouse_pos: ui.input(|i| i.pointer.hover_pos()),
-            best_dist_sq: f32::INFINITY,
+            enabled: is_mouse_over_tree,
+            dragged_tile_id,
+            payload,
+            dragging_files,
+            mouse_pos,
+            candidates: vec![],
+            hovered_tile: None,
+            compass: vec![],
             best_insertion: None,
             preview_rect: None,
         };
 
         if let Some(root) = self.root {
             self.tiles
-                .layout_tile(ui.style(), behavior, ui.available_rect_before_wrap(), root);
+                .layout_tile(ui.style(), behavior, tree_rect, root);
+            self.animate_layout(behavior, ui);
+
+            self.tile_ui(behavior, &mut drop_context, ui, root, false);
+        }
+        self.handle_duplicate_request(behavior, ui.ctx());
+        self.update_shown(behavior, ui.ctx().frame_nr());
+
+        if drop_context.is_dragging() && is_mouse_over_tree {
+            let payload = drop_context.payload.clone();
+            let is_allowed = |insertion_point: &InsertionPoint| {
+                if behavior.is_locked(&self.tiles, insertion_point.parent_id) {
+                    false // Nothing can be dropped into a locked tile
+                } else if let Some((_, payload)) = &payload {
+                    behavior.can_drop_payload(&self.tiles, payload, insertion_point)
+                } else if let Some(dragged_tile_id) = dragged_tile_id {
+                    behavior.can_drop(&self.tiles, dragged_tile_id, insertion_point)
+                } else {
+                    dragging_files && behavior.can_drop_files(&self.tiles, insertion_point)
+                }
+            };
+
+            match behavior.drop_target_mode() {
+                DropTargetMode::Nearest => {
+                    drop_context.pick_best(is_allowed);
+                }
+                DropTargetMode::Compass => {
+                    let targets = self.compass_targets(behavior, ui.style(), &drop_context);
+                    drop_context.pick_compass(targets, is_allowed);
+                }
+            }
+        }
+
+        if let Some((drag_id, payload)) = drop_context.payload.clone() {
+            self.drop_payload(behavior, &drop_context, ui, drag_id, &payload);
+        } else if dragging_files {
+            self.drop_files(behavior, &drop_context, ui);
+        } else {
+            self.preview_dragged_tile(behavior, &drop_context, ui, foreign_drag);
+        }
+    }
+
+    /// Duplicate the tab chosen in a tab context menu, if it is one of ours.
+    fn handle_duplicate_request(&mut self, behavior: &mut dyn Behavior<Pane>, ctx: &egui::Context) {
+        let id = duplicate_request_id(self.tiles.ui_id);
+        let Some(tile_id) = ctx.data_mut(|data| data.get_temp::<TileId>(id)) else { return; };
+        ctx.data_mut(|data| data.remove::<TileId>(id));
+
+        let copy = self.duplicate_with(tile_id, |pane| {
+            if behavior.can_duplicate_pane(pane) {
+                behavior.duplicate_pane(pane)
+            } else {
+                None
+            }
+        });
+        if let Some(copy) = copy {
+            behavior.on_tab_duplicated(&mut self.tiles, tile_id, copy);
+        }
+    }
+
+    /// Remember what was shown this frame, and tell the behavior about panes that were shown or hidden.
+    fn update_shown(&mut self, behavior: &mut dyn Behavior<Pane>, frame_nr: u64) {
+        let previous = std::mem::replace(&mut self.tiles.shown, self.tiles.rects.clone());
+        self.tiles.shown_frame_nr = Some(frame_nr);
+
+        for &tile_id in previous.keys() {
+            if !self.tiles.shown.contains_key(&tile_id) {
+                if let Some(Tile::Pane(pane)) = self.tiles.tiles.get_mut(&tile_id) {
+                    behavior.on_pane_hidden(tile_id, pane);
+                }
+            }
+        }
+        for &tile_id in self.tiles.shown.keys() {
+            if !previous.contains_key(&tile_id) {
+                if let Some(Tile::Pane(pane)) = self.tiles.tiles.get_mut(&tile_id) {
+                    behavior.on_pane_shown(tile_id, pane);
+                }
+            }
+        }
+    }
+
+    /// After a structural change, interpolate the tile rects from where they were shown before.
+    ///
+    /// Only the rects are affected, not the stored `Shares`.
+    fn animate_layout(&mut self, behavior: &dyn Behavior<Pane>, ui: &Ui) {
+        let animation_time = behavior.layout_animation_time();
+        if animation_time <= 0.0 {
+            return;
+        }
+
+        let data_id = ui.id().with("layout_animation");
+        let now = ui.input(|i| i.time);
+        let structure_hash = self.structure_hash();
+
+        let mut animation: LayoutAnimation = ui
+            .data_mut(|data| data.get_temp(data_id))
+            .unwrap_or_default();
+        if animation.structure_hash != structure_hash {
+            animation.structure_hash = structure_hash;
+            animation.start_time = now;
+            animation.from = std::mem::take(&mut animation.shown);
+        }
 
-            self.tile_ui(behavior, &mut drop_context, ui, root);
+        let t = ((now - animation.start_time) / animation_time as f64) as f32;
+        if t < 1.0 {
+            let t = t * t * (3.0 - 2.0 * t); // smoothstep
+            for (tile_id, rect) in &mut self.tiles.rects {
+                if let Some(from) = animation.from.get(tile_id) {
+                    *rect = Rect::from_min_max(
+                        from.min + (rect.min - from.min) * t,
+                        from.max + (rect.max - from.max) * t,
+                    );
+                }
+            }
+            ui.ctx().request_repaint();
         }
 
-        self.preview_dragged_tile(behavior, &drop_context, ui);
+        animation.shown = self.tiles.rects.clone();
+        ui.data_mut(|data| data.insert_temp(data_id, animation));
     }
 
+    /// A hash of which tiles are where, ignoring sizes.
+    fn structure_hash(&self) -> u64 {
+        use std::hash::{Hash as _, Hasher as _};
+
+        fn hash_tile<Pane>(
+            tiles: &Tiles<Pane>,
+            tile_id: TileId,
+            hasher: &mut std::collections::hash_map::DefaultHasher,
+        ) {
+            tile_id.hash(hasher);
+            if let Some(Tile::Container(container)) = tiles.get(tile_id) {
+                container.kind().hash(hasher);
+                container.children().len().hash(hasher);
+                for &child in container.children() {
+                    hash_tile(tiles, child, hasher);
+                }
+            }
+        }
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        if let Some(root) = self.root {
+            hash_tile(&self.tiles, root, &mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Show the given tile.
+    ///
+    /// `in_tabs` is true if the tile is the active child of a [`crate::Tabs`] container,
+    /// in which case the tab button is used as the header instead of a pane title bar.
     pub(super) fn tile_ui(
         &mut self,
         behavior: &mut dyn Behavior<Pane>,
         drop_context: &mut DropContext,
         ui: &mut Ui,
         tile_id: TileId,
+        in_tabs: bool,
     ) {
         // NOTE: important that we get the rect and tile in two steps,
         // otherwise we could loose the tile when there is no rect.
@@ -202,16 +559,40 @@ impl<Pane> Tree<Pane> {
             // Can't drag a tile onto self or any children
             drop_context.enabled = false;
         }
-        drop_context.on_tile(behavior, ui.style(), tile_id, rect, &tile);
+        drop_context.on_tile(behavior, ui.style(), tile_id, rect, &tile, in_tabs);
+
+        let mut content_rect = rect;
+        if let Tile::Pane(pane) = &mut tile {
+            if !in_tabs && behavior.show_pane_title_bar(tile_id, pane) {
+                let title_bar_height = behavior.pane_title_bar_height(ui.style());
+                let (title_bar_rect, rest) =
+                    rect.split_top_bottom_at_y(rect.top() + title_bar_height);
+                let mut title_bar_ui = Ui::new(
+                    ui.ctx().clone(),
+                    ui.layer_id(),
+                    ui.id().with((tile_id, "title_bar")),
+                    title_bar_rect,
+                    title_bar_rect,
+                );
+                title_bar_ui.set_enabled(ui.is_enabled());
+                let response = behavior.pane_title_bar_ui(&mut title_bar_ui, tile_id, pane);
+                if response.drag_started() && !self.is_root(tile_id) {
+                    ui.memory_mut(|mem| mem.set_dragged_id(tile_id.id()));
+                }
+                content_rect = rest;
+            }
+        }
 
         // Each tile gets its own `Ui`, nested inside each other, with proper clip rectangles.
+        let enabled = ui.is_enabled();
         let mut ui = egui::Ui::new(
             ui.ctx().clone(),
             ui.layer_id(),
             ui.id().with(tile_id),
-            rect,
-            rect,
+            content_rect,
+            content_rect,
         );
+        ui.set_enabled(enabled);
         match &mut tile {
             Tile::Pane(pane) => {
                 if behavior.pane_ui(&mut ui, tile_id, pane) == UiResponse::DragStarted {
@@ -227,6 +608,39 @@ impl<Pane> Tree<Pane> {
         drop_context.enabled = drop_context_was_enabled;
     }
 
+    /// The docking compass for the hovered tile, plus the outer edges of the root.
+    fn compass_targets(
+        &self,
+        behavior: &dyn Behavior<Pane>,
+        style: &egui::Style,
+        drop_context: &DropContext,
+    ) -> Vec<compass::CompassTarget> {
+        let button_size = behavior.compass_button_size(style);
+        let is_placeholder = |tile_id: TileId| matches!(self.tiles.get(tile_id), Some(Tile::Container(container)) if container.is_placeholder());
+
+        let mut targets = vec![];
+        if let Some((tile_id, rect)) = drop_context.hovered_tile {
+            let compass =
+                compass::tile_compass(tile_id, rect, button_size, behavior.tab_bar_height(style));
+            if is_placeholder(tile_id) {
+                // A placeholder can only be filled, so only offer the center:
+                targets.extend(
+                    compass
+                        .into_iter()
+                        .filter(|target| target.button == CompassButton::Center),
+                );
+            } else {
+                targets.extend(compass);
+            }
+        }
+        if let Some(root) = self.root {
+            if let (Some(rect), false) = (self.tiles.try_rect(root), is_placeholder(root)) {
+                targets.extend(compass::root_edges(root, rect, button_size));
+            }
+        }
+        targets
+    }
+
     /// Recursively "activate" the ancestors of the tiles that matches the given predicate.
     ///
     /// This means making the matching tiles and its ancestors the active tab in any tab layout.
@@ -241,10 +655,24 @@ impl<Pane> Tree<Pane> {
         behavior: &mut dyn Behavior<Pane>,
         drop_context: &DropContext,
         ui: &mut Ui,
+        foreign_drag: Option<DragSession>,
     ) {
         let (Some(mouse_pos), Some(dragged_tile_id)) =
             (drop_context.mouse_pos, drop_context.dragged_tile_id) else { return; };
 
+        if let Some(session) = foreign_drag {
+            // The tree owning the tile shows what is being dragged.
+            self.preview_drop(behavior, drop_context, ui, mouse_pos, dragged_tile_id.id());
+
+            if ui.input(|i| i.pointer.any_released()) {
+                if let Some(insertion_point) = drop_context.best_insertion {
+                    PendingTransfer::store(ui.ctx(), session, self.tiles.ui_id, insertion_point);
+                }
+                clear_smooth_preview_rect(ui.ctx(), dragged_tile_id.id());
+            }
+            return;
+        }
+
         ui.output_mut(|o| o.cursor_icon = egui::CursorIcon::Grabbing);
 
         // Preview what is being dragged:
@@ -256,14 +684,191 @@ impl<Pane> Tree<Pane> {
                 let mut frame = egui::Frame::popup(ui.style());
                 frame.fill = frame.fill.gamma_multiply(0.5); // Make see-through
                 frame.show(ui, |ui| {
-                    // TODO(emilk): preview contents?
                     let text = behavior.tab_title_for_tile(&self.tiles, dragged_tile_id);
                     ui.label(text);
+
+                    if let Some(size) = behavior.drag_thumbnail_size(ui.style()) {
+                        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                        self.preview_subtree_ui(behavior, ui, dragged_tile_id, rect);
+                    }
                 });
             });
 
+        self.preview_drop(behavior, drop_context, ui, mouse_pos, dragged_tile_id.id());
+
+        if ui.input(|i| i.pointer.any_released()) {
+            ui.memory_mut(|mem| mem.stop_dragging());
+            if let Some(insertion_point) = drop_context.best_insertion {
+                self.move_tile(dragged_tile_id, insertion_point);
+            }
+            clear_smooth_preview_rect(ui.ctx(), dragged_tile_id.id());
+        }
+    }
+
+    /// Preview where an external [`DragPayload`] would go, and turn it into a pane on release.
+    fn drop_payload(
+        &mut self,
+        behavior: &mut dyn Behavior<Pane>,
+        drop_context: &DropContext,
+        ui: &mut Ui,
+        drag_id: egui::Id,
+        payload: &DragPayload,
+    ) {
+        let Some(mouse_pos) = drop_context.mouse_pos else { return; };
+
+        self.preview_drop(behavior, drop_context, ui, mouse_pos, drag_id);
+
+        if ui.input(|i| i.pointer.any_released()) {
+            if let Some(insertion_point) = drop_context.best_insertion {
+                if let Some(pane) = behavior.create_pane_from_drop(payload) {
+                    let tile_id = self.tiles.insert_pane(pane);
+                    self.tiles.insert(insertion_point, tile_id);
+                    self.fill_placeholder(insertion_point.parent_id);
+                }
+            }
+            clear_smooth_preview_rect(ui.ctx(), drag_id);
+        }
+    }
+
+    /// Preview where hovered files would go, and hand them to [`Behavior::create_panes_from_files`] when dropped.
+    fn drop_files(
+        &mut self,
+        behavior: &mut dyn Behavior<Pane>,
+        drop_context: &DropContext,
+        ui: &mut Ui,
+    ) {
+        let Some(mouse_pos) = drop_context.mouse_pos else { return; };
+        let drag_id = ui.id().with("dropped_files");
+
+        self.preview_drop(behavior, drop_context, ui, mouse_pos, drag_id);
+
+        let dropped_files = ui.input(|i| i.raw.dropped_files.clone());
+        if !dropped_files.is_empty() {
+            if let Some(insertion_point) = drop_context.best_insertion {
+                let panes = behavior.create_panes_from_files(&dropped_files, &insertion_point);
+                self.insert_panes(panes, insertion_point);
+            }
+            clear_smooth_preview_rect(ui.ctx(), drag_id);
+        }
+    }
+
+    /// Insert the given panes next to each other, the first one at the given insertion point.
+    pub(super) fn insert_panes(&mut self, panes: Vec<Pane>, insertion_point: InsertionPoint) {
+        let InsertionPoint {
+            parent_id,
+            insertion,
+        } = insertion_point;
+
+        // `None` once the first pane is in a grid: the rest are appended and placed automatically.
+        let mut insertion = Some(insertion);
+
+        for pane in panes {
+            let tile_id = self.tiles.insert_pane(pane);
+            let Some(current) = insertion else {
+                if let Some(Tile::Container(parent)) = self.tiles.get_mut(parent_id) {
+                    parent.add_child(tile_id);
+                }
+                continue;
+            };
+            self.tiles
+                .insert(InsertionPoint::new(parent_id, current), tile_id);
+
+            // The parent now has the expected kind, so put the next one right after this one:
+            let Some(Tile::Container(parent)) = self.tiles.get(parent_id) else { break; };
+            let Some(index) = parent.children().iter().position(|&child| child == tile_id) else { break; };
+            insertion = match current {
+                ContainerInsertion::Tabs(_) => Some(ContainerInsertion::Tabs(index + 1)),
+                ContainerInsertion::Horizontal(_) => {
+                    Some(ContainerInsertion::Horizontal(index + 1))
+                }
+                ContainerInsertion::Vertical(_) => Some(ContainerInsertion::Vertical(index + 1)),
+                ContainerInsertion::Grid(_) => None,
+            };
+        }
+
+        self.fill_placeholder(parent_id);
+    }
+
+    /// Show a read-only copy of the given subtree in the given rect, e.g. under the mouse.
+    ///
+    /// This must not touch the state of the real tiles, which are shown elsewhere this frame:
+    /// it is laid out into a scratch rect map, gets its own id scope,
+    /// and has no tab buttons, splitters or drop zones.
+    fn preview_subtree_ui(
+        &mut self,
+        behavior: &mut dyn Behavior<Pane>,
+        ui: &mut Ui,
+        tile_id: TileId,
+        rect: Rect,
+    ) {
+        let real_rects = std::mem::take(&mut self.tiles.rects);
+        self.tiles.layout_tile(ui.style(), behavior, rect, tile_id);
+        let preview_rects = std::mem::replace(&mut self.tiles.rects, real_rects);
+
+        ui.push_id((tile_id, "preview"), |ui| {
+            let mut ui = ui.child_ui(rect, *ui.layout());
+            ui.set_enabled(false);
+            self.preview_tile_ui(behavior, &mut ui, &preview_rects, tile_id);
+        });
+    }
+
+    fn preview_tile_ui(
+        &mut self,
+        behavior: &mut dyn Behavior<Pane>,
+        ui: &mut Ui,
+        rects: &nohash_hasher::IntMap<TileId, Rect>,
+        tile_id: TileId,
+    ) {
+        let Some(&rect) = rects.get(&tile_id) else { return; }; // e.g. an inactive tab
+
+        let children = match self.tiles.get_mut(tile_id) {
+            Some(Tile::Pane(pane)) => {
+                let mut ui = Ui::new(
+                    ui.ctx().clone(),
+                    ui.layer_id(),
+                    ui.id().with(tile_id),
+                    rect,
+                    rect.intersect(ui.clip_rect()),
+                );
+                ui.set_enabled(false);
+                let _ = behavior.pane_ui(&mut ui, tile_id, pane);
+                return;
+            }
+            Some(Tile::Container(container)) => {
+                if let Container::Tabs(_) = container {
+                    let tab_bar_rect = rect
+                        .split_top_bottom_at_y(rect.top() + behavior.tab_bar_height(ui.style()))
+                        .0;
+                    ui.painter().rect_filled(
+                        tab_bar_rect,
+                        0.0,
+                        behavior.tab_bar_color(ui.visuals()),
+                    );
+                }
+                container.children().to_vec()
+            }
+            None => {
+                log::warn!("Failed to find tile {tile_id:?} during preview");
+                return;
+            }
+        };
+
+        for child in children {
+            self.preview_tile_ui(behavior, ui, rects, child);
+        }
+    }
+
+    /// Show where the dragged tile would end up if dropped now.
+    fn preview_drop(
+        &mut self,
+        behavior: &mut dyn Behavior<Pane>,
+        drop_context: &DropContext,
+        ui: &mut Ui,
+        mouse_pos: egui::Pos2,
+        drag_id: egui::Id,
+    ) {
         if let Some(preview_rect) = drop_context.preview_rect {
-            let preview_rect = smooth_preview_rect(ui.ctx(), dragged_tile_id, preview_rect);
+            let preview_rect = smooth_preview_rect(ui.ctx(), drag_id, preview_rect);
 
             let parent_rect = drop_context
                 .best_insertion
@@ -271,30 +876,69 @@ impl<Pane> Tree<Pane> {
 
             behavior.paint_drag_preview(ui.visuals(), ui.painter(), parent_rect, preview_rect);
 
-            if behavior.preview_dragged_panes() {
-                // TODO(emilk): add support for previewing containers too.
+            if let Some(dragged_tile_id) = drop_context.dragged_tile_id {
                 if preview_rect.width() > 32.0 && preview_rect.height() > 32.0 {
-                    if let Some(Tile::Pane(pane)) = self.tiles.get_mut(dragged_tile_id) {
-                        let _ = behavior.pane_ui(
-                            &mut ui.child_ui(preview_rect, *ui.layout()),
-                            dragged_tile_id,
-                            pane,
-                        );
+                    match self.tiles.get_mut(dragged_tile_id) {
+                        Some(Tile::Pane(pane)) if behavior.preview_dragged_panes() => {
+                            ui.push_id((dragged_tile_id, "preview"), |ui| {
+                                let _ = behavior.pane_ui(
+                                    &mut ui.child_ui(preview_rect, *ui.layout()),
+                                    dragged_tile_id,
+                                    pane,
+                                );
+                            });
+                        }
+                        Some(Tile::Container(_)) if behavior.preview_dragged_containers() => {
+                            self.preview_subtree_ui(behavior, ui, dragged_tile_id, preview_rect);
+                        }
+                        _ => {}
                     }
                 }
             }
         }
 
-        if ui.input(|i| i.pointer.any_released()) {
-            ui.memory_mut(|mem| mem.stop_dragging());
-            if let Some(insertion_point) = drop_context.best_insertion {
-                self.move_tile(dragged_tile_id, insertion_point);
-            }
-            clear_smooth_preview_rect(ui.ctx(), dragged_tile_id);
+        for target in &drop_context.compass {
+            let hovered = target.button_rect.contains(mouse_pos);
+            behavior.paint_compass_button(
+                ui.visuals(),
+                ui.painter(),
+                target.button_rect,
+                target.button,
+                hovered,
+            );
         }
     }
 
-    fn simplify(&mut self, options: &SimplificationOptions) {
+    /// Fix cycles and duplicated tiles, remove the panes for which [`Behavior::retain_pane`] returns `false`,
+    /// and free the tiles that can't be reached from the root.
+    ///
+    /// [`Self::ui`] calls this for you every frame, whatever the [`Behavior::simplification_mode`].
+    pub fn gc(&mut self, behavior: &mut dyn Behavior<Pane>) {
+        self.tiles.gc_root(behavior, self.root);
+    }
+
+    /// Simplify the tree, e.g. removing empty containers, as allowed by the given options.
+    ///
+    /// [`Self::ui`] calls this for you, unless [`Behavior::simplification_mode`] says otherwise.
+    /// Containers with [`crate::Tabs::keep_if_empty`] set are never removed.
+    ///
+    /// ```
+    /// use egui_tiles::{Container, SimplificationOptions, Tiles, Tree};
+    ///
+    /// let mut tiles = Tiles::default();
+    /// let pane = tiles.insert_pane("pane");
+    /// let mut placeholder = Container::new_tabs(vec![]);
+    /// placeholder.set_keep_if_empty(true);
+    /// let placeholder = tiles.insert_container(placeholder);
+    /// let empty = tiles.insert_tab_tile(vec![]);
+    /// let root = tiles.insert_horizontal_tile(vec![pane, placeholder, empty]);
+    ///
+    /// let mut tree = Tree::new(root, tiles);
+    /// tree.simplify(&SimplificationOptions::default());
+    /// assert!(tree.tiles.get(placeholder).is_some());
+    /// assert!(tree.tiles.get(empty).is_none());
+    /// ```
+    pub fn simplify(&mut self, options: &SimplificationOptions) {
         if let Some(root) = self.root {
             match self.tiles.simplify(options, root, None) {
                 SimplifyAction::Keep => {}
@@ -306,6 +950,12 @@ impl<Pane> Tree<Pane> {
                 }
             }
         }
+
+        if options.all_panes_must_have_tabs {
+            if let Some(root) = self.root {
+                self.tiles.make_all_panes_children_of_tabs(false, root);
+            }
+        }
     }
 
     /// Move the given tile to the given insertion point.
@@ -316,6 +966,45 @@ impl<Pane> Tree<Pane> {
         );
         self.remove_tile_id_from_parent(moved_tile_id);
         self.tiles.insert(insertion_point, moved_tile_id);
+        self.fill_placeholder(insertion_point.parent_id);
+    }
+
+    /// If the given tile is a placeholder that was just dropped into, let what was dropped take its place.
+    ///
+    /// If several tiles were dropped at once, the placeholder is kept as an ordinary [`crate::Tabs`].
+    pub(super) fn fill_placeholder(&mut self, tile_id: TileId) {
+        let Some(Tile::Container(Container::Tabs(tabs))) = self.tiles.get_mut(tile_id) else { return; };
+        if tabs.placeholder.is_none() || tabs.children.is_empty() {
+            return;
+        }
+        tabs.placeholder = None;
+        let [child] = tabs.children[..] else { return; };
+
+        self.tiles.tiles.remove(&tile_id);
+        if self.is_root(tile_id) {
+            self.root = Some(child);
+        } else {
+            for tile in self.tiles.tiles.values_mut() {
+                if let Tile::Container(container) = tile {
+                    container.simplify_children(|it| {
+                        if it == tile_id {
+                            SimplifyAction::Replace(child)
+                        } else {
+                            SimplifyAction::Keep
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    /// Is the given tile allowed to be dragged?
+    ///
+    /// The root and locked tiles never are.
+    fn can_drag(&self, behavior: &dyn Behavior<Pane>, tile_id: TileId) -> bool {
+        !self.is_root(tile_id)
+            && !behavior.is_locked(&self.tiles, tile_id)
+            && behavior.can_drag(&self.tiles, tile_id)
     }
 
     /// Find the currently dragged tile, if any.
@@ -345,6 +1034,245 @@ impl<Pane> Tree<Pane> {
         None
     }
 
+    /// Remove the given tile and all its descendants, and return them as a tree of their own.
+    ///
+    /// The tiles keep their ids, so the result can be put back with [`Self::graft`],
+    /// or saved as a template with e.g. [`Self::to_layout`].
+    ///
+    /// ```
+    /// use egui_tiles::{ContainerInsertion, InsertionPoint, Tree};
+    ///
+    /// let mut tree = Tree::from_layout_string("h[a, v[b, c]]", |name| Some(name.to_owned())).unwrap();
+    /// let b = tree.find(|_, tile| matches!(tile, egui_tiles::Tile::Pane(pane) if pane == "b")).unwrap();
+    /// let group = tree.ancestors(b)[0];
+    ///
+    /// let template = tree.extract_subtree(group);
+    /// assert_eq!(template.to_layout_string(|pane| pane.clone()), "v[b, c]");
+    /// assert_eq!(tree.to_layout_string(|pane| pane.clone()), "h[a]");
+    ///
+    /// // Insert two copies. The second one gets new ids, as the first one uses the original ones.
+    /// let root = tree.root().unwrap();
+    /// tree.graft(template.clone(), InsertionPoint::new(root, ContainerInsertion::Horizontal(1)));
+    /// tree.graft(template, InsertionPoint::new(root, ContainerInsertion::Horizontal(0)));
+    /// assert_eq!(tree.to_layout_string(|pane| pane.clone()), "h[v[b, c], a, v[b, c]]");
+    /// ```
+    pub fn extract_subtree(&mut self, tile_id: TileId) -> Self {
+        // Also drops its share and grid location, and picks another active tab if needed:
+        for parent in self.tiles.tiles.values_mut() {
+            if let Tile::Container(container) = parent {
+                container.simplify_children(|child| {
+                    if child == tile_id {
+                        SimplifyAction::Remove
+                    } else {
+                        SimplifyAction::Keep
+                    }
+                });
+            }
+        }
+        if self.is_root(tile_id) {
+            self.root = None;
+        }
+
+        let mut subtree = Tiles {
+            id_allocator: self.tiles.id_allocator,
+            ..Default::default()
+        };
+        let mut stack = vec![tile_id];
+        while let Some(tile_id) = stack.pop() {
+            self.tiles.rects.remove(&tile_id);
+            let Some(tile) = self.tiles.tiles.remove(&tile_id) else {
+                log::warn!("Failed to find tile {tile_id:?} while removing subtree");
+                continue;
+            };
+            if let Tile::Container(container) = &tile {
+                stack.extend(container.children().iter().rev());
+            }
+            subtree.tiles.insert(tile_id, tile);
+        }
+
+        if subtree.tiles.is_empty() {
+            Self::empty()
+        } else {
+            Self::new(tile_id, subtree)
+        }
+    }
+
+    /// Insert all tiles reachable from the root of `other` at the given insertion point,
+    /// e.g. a tree returned by [`Self::extract_subtree`].
+    ///
+    /// Any tile whose id is already in use in this tree is given a new one,
+    /// with [`crate::Linear::shares`] and [`crate::Grid::locations`] updated to match.
+    /// If this tree is empty, the root of `other` becomes the new root.
+    ///
+    /// Returns the (possibly new) id of the root of `other`, or `None` if `other` is empty.
+    pub fn graft(&mut self, mut other: Self, insertion_point: InsertionPoint) -> Option<TileId> {
+        let subtree_root = other.root?;
+        let subtree = other.extract_subtree(subtree_root).tiles.tiles;
+        let subtree_root = self.adopt_tiles(subtree, subtree_root);
+
+        if self.root.is_some() {
+            self.tiles.insert(insertion_point, subtree_root);
+            self.fill_placeholder(insertion_point.parent_id);
+        } else {
+            self.root = Some(subtree_root);
+        }
+        Some(subtree_root)
+    }
+
+    /// Add the given tiles to this tree, without attaching them to any parent.
+    ///
+    /// Any tile whose id is already in use is given a new one.
+    /// Returns the (possibly new) id of `subtree_root`.
+    pub(super) fn adopt_tiles(
+        &mut self,
+        subtree: nohash_hasher::IntMap<TileId, Tile<Pane>>,
+        subtree_root: TileId,
+    ) -> TileId {
+        self.tiles.key_index.invalidate();
+
+        let mut remapped: nohash_hasher::IntMap<TileId, TileId> = Default::default();
+        for &tile_id in subtree.keys() {
+            if self.tiles.tiles.contains_key(&tile_id) {
+                let new_id = self.tiles.next_free_id(|id| {
+                    subtree.contains_key(&id) || remapped.values().any(|&used| used == id)
+                });
+                remapped.insert(tile_id, new_id);
+            }
+        }
+        let new_id = |tile_id: TileId| remapped.get(&tile_id).copied().unwrap_or(tile_id);
+
+        for (tile_id, mut tile) in subtree {
+            if let Tile::Container(container) = &mut tile {
+                container.simplify_children(|child| match remapped.get(&child) {
+                    Some(&new_child) => SimplifyAction::Replace(new_child),
+                    None => SimplifyAction::Keep,
+                });
+            }
+            self.tiles.tiles.insert(new_id(tile_id), tile);
+        }
+
+        new_id(subtree_root)
+    }
+
+    /// Make a deep copy of the given tile and its descendants, with new ids,
+    /// and insert it right after the original.
+    ///
+    /// The copy of a tab is made the active tab, and the copy of a child of a
+    /// [`crate::Linear`] gets the same share as the original.
+    /// Duplicating the root puts the original and the copy side by side.
+    ///
+    /// Returns the id of the copy, or `None` if the tile does not exist.
+    ///
+    /// ```
+    /// use egui_tiles::Tree;
+    ///
+    /// let mut tree = Tree::from_layout_string("tabs[a, h[b, c]]", |name| Some(name.to_owned())).unwrap();
+    /// let b = tree.find(|_, tile| matches!(tile, egui_tiles::Tile::Pane(pane) if pane == "b")).unwrap();
+    /// let group = tree.ancestors(b)[0];
+    ///
+    /// let copy = tree.duplicate(group).unwrap();
+    /// assert_ne!(copy, group);
+    /// assert_eq!(tree.to_layout_string(|pane| pane.clone()), "tabs[a, h[b, c], *h[b, c]]");
+    /// assert_eq!(tree.validate(), Ok(()));
+    /// ```
+    pub fn duplicate(&mut self, tile_id: TileId) -> Option<TileId>
+    where
+        Pane: Clone,
+    {
+        self.duplicate_with(tile_id, |pane| Some(pane.clone()))
+    }
+
+    /// Like [`Self::duplicate`], but with your own way of copying panes.
+    ///
+    /// Panes for which `clone_pane` returns `None` are left out of the copy.
+    /// Returns `None`, and changes nothing, if the tile does not exist or none of its panes were copied.
+    pub fn duplicate_with(
+        &mut self,
+        tile_id: TileId,
+        mut clone_pane: impl FnMut(&Pane) -> Option<Pane>,
+    ) -> Option<TileId> {
+        let mut copy: nohash_hasher::IntMap<TileId, Tile<Pane>> = Default::default();
+        let mut stack = vec![tile_id];
+        while let Some(it) = stack.pop() {
+            let tile = match self.tiles.get(it) {
+                Some(Tile::Pane(pane)) => match clone_pane(pane) {
+                    Some(pane) => Tile::Pane(pane),
+                    None => continue,
+                },
+                Some(Tile::Container(container)) => {
+                    stack.extend(container.children());
+                    Tile::Container(container.clone())
+                }
+                None => {
+                    log::warn!("Failed to find tile {it:?} while duplicating {tile_id:?}");
+                    continue;
+                }
+            };
+            copy.insert(it, tile);
+        }
+        if !copy.values().any(|tile| matches!(tile, Tile::Pane(_))) {
+            return None; // Nothing worth copying
+        }
+
+        // Forget about the panes that were not copied, along with their shares, locations and active tab:
+        let copied: nohash_hasher::IntSet<TileId> = copy.keys().copied().collect();
+        for tile in copy.values_mut() {
+            if let Tile::Container(container) = tile {
+                container.simplify_children(|child| {
+                    if copied.contains(&child) {
+                        SimplifyAction::Keep
+                    } else {
+                        SimplifyAction::Remove
+                    }
+                });
+            }
+        }
+
+        // All ids are in use by the original, so the copy gets new ones:
+        let copy_id = self.adopt_tiles(copy, tile_id);
+
+        if let Some(parent_id) = self.tiles.parent_of(tile_id) {
+            match self.tiles.get_mut(parent_id) {
+                Some(Tile::Container(Container::Tabs(tabs))) => {
+                    insert_after(&mut tabs.children, tile_id, copy_id);
+                    tabs.set_active(copy_id);
+                }
+                Some(Tile::Container(Container::Linear(linear))) => {
+                    insert_after(&mut linear.children, tile_id, copy_id);
+                    linear.shares[copy_id] = linear.shares[tile_id];
+                }
+                Some(Tile::Container(Container::Grid(grid))) => {
+                    insert_after(&mut grid.children, tile_id, copy_id);
+                }
+                Some(Tile::Pane(_)) | None => {}
+            }
+        } else if self.is_root(tile_id) {
+            self.root = Some(self.tiles.insert_horizontal_tile(vec![tile_id, copy_id]));
+        } else {
+            log::warn!("The copy of {tile_id:?} has no parent, as the original has none");
+        }
+
+        Some(copy_id)
+    }
+
+    /// Put `new` where `old` was: among the children of `parent_id`, or at the root.
+    ///
+    /// `new` takes over the share, grid location or active tab status of `old`.
+    pub(super) fn replace_child(&mut self, parent_id: Option<TileId>, old: TileId, new: TileId) {
+        if let Some(Tile::Container(parent)) = parent_id.and_then(|id| self.tiles.get_mut(id)) {
+            parent.simplify_children(|child| {
+                if child == old {
+                    SimplifyAction::Replace(new)
+                } else {
+                    SimplifyAction::Keep
+                }
+            });
+        }
+        if self.is_root(old) {
+            self.root = Some(new);
+        }
+    }
+
     /// This removes the given tile from the parents list of children.
     ///
     /// The [`Tile`] itself is not removed from [`Self::tiles`].
@@ -361,20 +1289,73 @@ impl<Pane> Tree<Pane> {
 
 // ----------------------------------------------------------------------------
 
+/// The state of [`Tree::animate_layout`].
+///
+/// Stored in egui temp storage, just like the smoothed preview rect.
+#[derive(Clone, Default)]
+struct LayoutAnimation {
+    structure_hash: u64,
+    start_time: f64,
+
+    /// Where the tiles were shown when the structure last changed.
+    from: nohash_hasher::IntMap<TileId, Rect>,
+
+    /// Where the tiles were shown last frame.
+    shown: nohash_hasher::IntMap<TileId, Rect>,
+}
+
+/// Where a tab context menu stores the tile to duplicate, until [`Tree::ui`] of the same tree gets to it.
+fn duplicate_request_id(tree_ui_id: egui::Id) -> egui::Id {
+    tree_ui_id.with("egui_tiles_duplicate_tab")
+}
+
+/// Should the tab of the given tile offer to duplicate it, i.e. is there anything in it that can be copied?
+pub(super) fn can_duplicate_tab<Pane>(
+    tiles: &Tiles<Pane>,
+    behavior: &dyn Behavior<Pane>,
+    tile_id: TileId,
+) -> bool {
+    if !behavior.can_duplicate_tab(tiles, tile_id) {
+        return false;
+    }
+    let mut stack = vec![tile_id];
+    while let Some(it) = stack.pop() {
+        match tiles.get(it) {
+            Some(Tile::Pane(pane)) if behavior.can_duplicate_pane(pane) => return true,
+            Some(Tile::Container(container)) => stack.extend(container.children()),
+            Some(Tile::Pane(_)) | None => {}
+        }
+    }
+    false
+}
+
+pub(super) fn request_duplicate<Pane>(ctx: &egui::Context, tree: &Tree<Pane>, tile_id: TileId) {
+    let id = duplicate_request_id(tree.tiles.ui_id);
+    ctx.data_mut(|data| data.insert_temp(id, tile_id));
+}
+
+fn insert_after(children: &mut Vec<TileId>, existing: TileId, new: TileId) {
+    let index = children
+        .iter()
+        .position(|&child| child == existing)
+        .map_or(children.len(), |index| index + 1);
+    children.insert(index, new);
+}
+
 /// We store the preview rect in egui temp storage so that it is not serialized,
 /// and so that a user could re-create the [`Tree`] each frame and still get smooth previews.
-fn smooth_preview_rect_id(dragged_tile_id: TileId) -> egui::Id {
-    egui::Id::new((dragged_tile_id, "smoothed_preview_rect"))
+fn smooth_preview_rect_id(drag_id: egui::Id) -> egui::Id {
+    drag_id.with("smoothed_preview_rect")
 }
 
-fn clear_smooth_preview_rect(ctx: &egui::Context, dragged_tile_id: TileId) {
-    let data_id = smooth_preview_rect_id(dragged_tile_id);
+fn clear_smooth_preview_rect(ctx: &egui::Context, drag_id: egui::Id) {
+    let data_id = smooth_preview_rect_id(drag_id);
     ctx.data_mut(|data| data.remove::<Rect>(data_id));
 }
 
 /// Take the preview rectangle and smooth it over time.
-fn smooth_preview_rect(ctx: &egui::Context, dragged_tile_id: TileId, new_rect: Rect) -> Rect {
-    let data_id = smooth_preview_rect_id(dragged_tile_id);
+fn smooth_preview_rect(ctx: &egui::Context, drag_id: egui::Id, new_rect: Rect) -> Rect {
+    let data_id = smooth_preview_rect_id(drag_id);
 
     let dt = ctx.input(|input| input.stable_dt).at_most(0.1);
 
@@ -402,3 +1383,178 @@ fn smooth_preview_rect(ctx: &egui::Context, dragged_tile_id: TileId, new_rect: R
 
     smoothed
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GridLoc;
+
+    #[test]
+    fn insert_panes_into_grid_keeps_all_panes() {
+        let mut tree = Tree::new_grid(vec!["a"]);
+        let root = tree.root().expect("the tree has a root");
+        let loc = GridLoc::from_col_row(1, 0);
+        tree.insert_panes(
+            vec!["b", "c", "d"],
+            InsertionPoint::new(root, ContainerInsertion::Grid(loc)),
+        );
+
+        let Some(Tile::Container(Container::Grid(grid))) = tree.tiles.get(root) else {
+            panic!("root should still be a grid");
+        };
+        assert_eq!(grid.children.len(), 4);
+        let at_loc: Vec<TileId> = grid
+            .locations
+            .iter()
+            .filter(|(_, &l)| l == loc)
+            .map(|(&id, _)| id)
+            .collect();
+        assert_eq!(at_loc.len(), 1);
+        assert_eq!(tree.tiles.get(at_loc[0]), Some(&Tile::Pane("b")));
+    }
+
+    /// Counts how often each pane was reported shown and hidden.
+    #[derive(Default)]
+    struct Visibility {
+        shown: usize,
+        hidden: usize,
+    }
+
+    impl Behavior<&str> for Visibility {
+        fn pane_ui(&mut self, _ui: &mut Ui, _tile_id: TileId, _pane: &mut &str) -> UiResponse {
+            UiResponse::None
+        }
+
+        fn tab_title_for_pane(&mut self, pane: &&str) -> egui::WidgetText {
+            (*pane).into()
+        }
+
+        fn on_pane_shown(&mut self, _tile_id: TileId, _pane: &mut &str) {
+            self.shown += 1;
+        }
+
+        fn on_pane_hidden(&mut self, _tile_id: TileId, _pane: &mut &str) {
+            self.hidden += 1;
+        }
+    }
+
+    #[test]
+    fn panes_of_a_tree_that_is_not_shown_are_not_visible() {
+        let mut tree = Tree::new_horizontal(vec!["a"]);
+        let mut behavior = Visibility::default();
+        let ctx = egui::Context::default();
+        let frame = |show: bool, tree: &mut Tree<&str>, behavior: &mut Visibility| {
+            let _ = ctx.run(Default::default(), |ctx| {
+                if show {
+                    egui::CentralPanel::default().show(ctx, |ui| tree.ui(behavior, ui));
+                }
+            });
+        };
+
+        frame(true, &mut tree, &mut behavior);
+        let (a, _) = tree.shown_panes(&ctx).next().expect("a is shown");
+        assert!(tree.is_visible(&ctx, a));
+        assert_eq!((behavior.shown, behavior.hidden), (1, 0));
+
+        frame(false, &mut tree, &mut behavior);
+        frame(false, &mut tree, &mut behavior);
+        assert!(!tree.is_visible(&ctx, a));
+        assert_eq!(tree.shown_panes(&ctx).count(), 0);
+
+        frame(true, &mut tree, &mut behavior);
+        assert!(tree.is_visible(&ctx, a));
+        assert_eq!((behavior.shown, behavior.hidden), (1, 0));
+    }
+
+    /// Copies every pane.
+    struct Copying;
+
+    impl Behavior<&'static str> for Copying {
+        fn pane_ui(
+            &mut self,
+            _ui: &mut Ui,
+            _tile_id: TileId,
+            _pane: &mut &'static str,
+        ) -> UiResponse {
+            UiResponse::None
+        }
+
+        fn tab_title_for_pane(&mut self, pane: &&'static str) -> egui::WidgetText {
+            (*pane).into()
+        }
+
+        fn can_duplicate_pane(&self, _pane: &&'static str) -> bool {
+            true
+        }
+
+        fn duplicate_pane(&mut self, pane: &&'static str) -> Option<&'static str> {
+            Some(*pane)
+        }
+    }
+
+    #[test]
+    fn duplicate_requests_stay_in_their_tree() {
+        let mut left = Tree::new_tabs(vec!["a"]);
+        let mut right = left.clone(); // Same tile ids
+        let ctx = egui::Context::default();
+        let frame = |left: &mut Tree<&'static str>, right: &mut Tree<&'static str>| {
+            let _ = ctx.run(Default::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.push_id("left", |ui| left.ui(&mut Copying, ui));
+                    ui.push_id("right", |ui| right.ui(&mut Copying, ui));
+                });
+            });
+        };
+
+        frame(&mut left, &mut right);
+        let (a, _) = left.panes().next().expect("a is in the tree");
+        request_duplicate(&ctx, &right, a);
+        frame(&mut left, &mut right);
+
+        assert_eq!(left.panes().count(), 1);
+        assert_eq!(right.panes().count(), 2);
+    }
+
+    #[test]
+    fn extract_subtree_leaves_a_valid_tree() {
+        let mut tree =
+            Tree::from_layout_string("h[a, 2:tabs[b, *c]]", |name| Some(name.to_owned()))
+                .expect("valid layout");
+        let c = tree
+            .find(|_, tile| matches!(tile, Tile::Pane(pane) if pane == "c"))
+            .expect("c is in the tree");
+
+        let tabs = tree.ancestors(c)[0];
+        tree.extract_subtree(c);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.to_layout_string(Clone::clone), "h[a, 2:tabs[b]]");
+
+        tree.extract_subtree(tabs);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.to_layout_string(Clone::clone), "h[a]");
+    }
+
+    #[test]
+    fn duplicate_leaves_out_panes_cleanly() {
+        let mut tiles = Tiles::default();
+        let a = tiles.insert_pane("a");
+        let b = tiles.insert_pane("b");
+        let mut tabs = crate::Tabs::new(vec![a, b]);
+        tabs.set_active(b);
+        let tabs = tiles.insert_container(tabs);
+        let root = tiles.insert_horizontal_tile(vec![tabs]);
+        let mut tree = Tree::new(root, tiles);
+
+        assert_eq!(tree.duplicate_with(tabs, |_| None), None);
+
+        let copy = tree
+            .duplicate_with(tabs, |&pane| (pane == "a").then_some(pane))
+            .expect("a can be copied");
+        let Some(Tile::Container(Container::Tabs(copy))) = tree.tiles.get(copy) else {
+            panic!("the copy should be tabs");
+        };
+        assert_eq!(copy.children.len(), 1);
+        assert_eq!(copy.active, copy.children.first().copied());
+        assert_eq!(tree.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,274 @@
+//! Finding and fixing invalid state in a [`Tree`], e.g. after loading a corrupt layout.
+
+use super::{Container, Tile, TileId, Tiles, Tree};
+
+/// Something wrong with a [`Tree`], found by [`Tree::validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeError {
+    /// The root refers to a tile that does not exist.
+    DanglingRoot(TileId),
+
+    /// A container refers to a child that does not exist.
+    DanglingChild { parent: TileId, child: TileId },
+
+    /// A container lists the same child more than once.
+    DuplicateChild { parent: TileId, child: TileId },
+
+    /// A container has one of its own ancestors (or itself) as a child.
+    Cycle { parent: TileId, child: TileId },
+
+    /// A tile is the child of more than one container.
+    ///
+    /// Reported for all but the first parent, in depth-first order.
+    SharedChild { parent: TileId, child: TileId },
+
+    /// A tile that can not be reached from the root.
+    Orphan(TileId),
+
+    /// The `Shares` of a [`crate::Linear`] has an entry for a tile that is not one of its children.
+    StaleShare { container: TileId, tile: TileId },
+
+    /// [`crate::Grid::locations`] has an entry for a tile that is not one of its children.
+    StaleGridLocation { container: TileId, tile: TileId },
+
+    /// [`crate::Tabs::active`] is not one of its children.
+    InvalidActiveTab { container: TileId, active: TileId },
+}
+
+impl std::fmt::Display for TreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DanglingRoot(root) => write!(f, "the root {root:?} does not exist"),
+            Self::DanglingChild { parent, child } => {
+                write!(
+                    f,
+                    "{parent:?} has the child {child:?}, which does not exist"
+                )
+            }
+            Self::DuplicateChild { parent, child } => {
+                write!(f, "{parent:?} has the child {child:?} more than once")
+            }
+            Self::Cycle { parent, child } => {
+                write!(f, "{parent:?} has its ancestor {child:?} as a child")
+            }
+            Self::SharedChild { parent, child } => {
+                write!(
+                    f,
+                    "{parent:?} has the child {child:?}, which already has a parent"
+                )
+            }
+            Self::Orphan(tile_id) => write!(f, "{tile_id:?} can not be reached from the root"),
+            Self::StaleShare { container, tile } => {
+                write!(
+                    f,
+                    "{container:?} has a share for {tile:?}, which is not a child"
+                )
+            }
+            Self::StaleGridLocation { container, tile } => {
+                write!(
+                    f,
+                    "{container:?} has a location for {tile:?}, which is not a child"
+                )
+            }
+            Self::InvalidActiveTab { container, active } => {
+                write!(
+                    f,
+                    "the active tab {active:?} of {container:?} is not a child"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+impl<Pane> Tree<Pane> {
+    /// Check that the tree is well-formed, listing everything that is wrong with it.
+    ///
+    /// [`Self::ui`] quietly fixes most of these problems,
+    /// but this tells you precisely what was wrong, e.g. with a layout loaded from disk.
+    ///
+    /// ```
+    /// use egui_tiles::{Tiles, Tree, TreeError};
+    ///
+    /// let mut tiles = Tiles::default();
+    /// let a = tiles.insert_pane("a");
+    /// let root = tiles.insert_horizontal_tile(vec![a, a]);
+    /// let mut tree = Tree::new(root, tiles);
+    ///
+    /// let errors = vec![TreeError::DuplicateChild { parent: root, child: a }];
+    /// assert_eq!(tree.validate(), Err(errors.clone()));
+    /// assert_eq!(tree.repair(), errors);
+    /// assert_eq!(tree.validate(), Ok(()));
+    /// ```
+    ///
+    /// # Errors
+    /// All the problems found, parents before children.
+    pub fn validate(&self) -> Result<(), Vec<TreeError>> {
+        let mut errors = vec![];
+        let mut visited = nohash_hasher::IntSet::default();
+
+        if let Some(root) = self.root {
+            if self.tiles.get(root).is_some() {
+                let mut path = vec![];
+                validate_tile(&self.tiles, root, &mut path, &mut visited, &mut errors);
+            } else {
+                errors.push(TreeError::DanglingRoot(root));
+            }
+        }
+
+        let mut orphans: Vec<TileId> = self
+            .tiles
+            .tiles
+            .keys()
+            .filter(|tile_id| !visited.contains(tile_id))
+            .copied()
+            .collect();
+        orphans.sort_by_key(TileId::as_u64);
+        errors.extend(orphans.into_iter().map(TreeError::Orphan));
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Fix everything [`Self::validate`] complains about, returning what was fixed.
+    ///
+    /// Bad child references are removed, and so are orphaned tiles.
+    /// An invalid active tab is replaced with the first tab.
+    pub fn repair(&mut self) -> Vec<TreeError> {
+        let errors = self.validate().err().unwrap_or_default();
+
+        for error in &errors {
+            match *error {
+                TreeError::DanglingRoot(_) => {
+                    self.root = None;
+                }
+                TreeError::DanglingChild { parent, child }
+                | TreeError::Cycle { parent, child }
+                | TreeError::SharedChild { parent, child } => {
+                    if let Some(Tile::Container(container)) = self.tiles.get_mut(parent) {
+                        container.retain(|tile_id| tile_id != child);
+                    }
+                }
+                TreeError::DuplicateChild { parent, child } => {
+                    if let Some(Tile::Container(container)) = self.tiles.get_mut(parent) {
+                        let mut is_first = true;
+                        container.retain(|tile_id| {
+                            if tile_id != child {
+                                true
+                            } else {
+                                std::mem::replace(&mut is_first, false)
+                            }
+                        });
+                    }
+                }
+                TreeError::Orphan(tile_id) => {
+                    self.tiles.tiles.remove(&tile_id);
+                    self.tiles.rects.remove(&tile_id);
+                }
+                TreeError::StaleShare { .. }
+                | TreeError::StaleGridLocation { .. }
+                | TreeError::InvalidActiveTab { .. } => {
+                    // Fixed below, together with the entries made stale by the fixes above.
+                }
+            }
+        }
+
+        for tile in self.tiles.tiles.values_mut() {
+            let Tile::Container(container) = tile else { continue; };
+            let children = container.children().to_vec();
+            match container {
+                Container::Tabs(tabs) => {
+                    if matches!(tabs.active, Some(active) if !children.contains(&active)) {
+                        tabs.active = children.first().copied();
+                    }
+                }
+                Container::Linear(linear) => {
+                    linear.shares.retain(|tile_id| children.contains(&tile_id));
+                }
+                Container::Grid(grid) => {
+                    grid.locations
+                        .retain(|tile_id, _| children.contains(tile_id));
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+fn validate_tile<Pane>(
+    tiles: &Tiles<Pane>,
+    it: TileId,
+    path: &mut Vec<TileId>,
+    visited: &mut nohash_hasher::IntSet<TileId>,
+    errors: &mut Vec<TreeError>,
+) {
+    visited.insert(it);
+    let Some(Tile::Container(container)) = tiles.get(it) else { return; };
+    let children = container.children();
+
+    path.push(it);
+    let mut seen = nohash_hasher::IntSet::default();
+    for &child in children {
+        let error = if !seen.insert(child) {
+            Some(TreeError::DuplicateChild { parent: it, child })
+        } else if tiles.get(child).is_none() {
+            Some(TreeError::DanglingChild { parent: it, child })
+        } else if path.contains(&child) {
+            Some(TreeError::Cycle { parent: it, child })
+        } else if visited.contains(&child) {
+            Some(TreeError::SharedChild { parent: it, child })
+        } else {
+            None
+        };
+
+        if let Some(error) = error {
+            errors.push(error);
+        } else {
+            validate_tile(tiles, child, path, visited, errors);
+        }
+    }
+    path.pop();
+
+    match container {
+        Container::Tabs(tabs) => {
+            if let Some(active) = tabs.active {
+                if !children.contains(&active) {
+                    errors.push(TreeError::InvalidActiveTab {
+                        container: it,
+                        active,
+                    });
+                }
+            }
+        }
+        Container::Linear(linear) => {
+            let mut stale: Vec<TileId> = (&linear.shares)
+                .into_iter()
+                .map(|(&tile_id, _)| tile_id)
+                .filter(|tile_id| !children.contains(tile_id))
+                .collect();
+            stale.sort_by_key(TileId::as_u64);
+            errors.extend(stale.into_iter().map(|tile| TreeError::StaleShare {
+                container: it,
+                tile,
+            }));
+        }
+        Container::Grid(grid) => {
+            let mut stale: Vec<TileId> = grid
+                .locations
+                .keys()
+                .filter(|tile_id| !children.contains(tile_id))
+                .copied()
+                .collect();
+            stale.sort_by_key(TileId::as_u64);
+            errors.extend(stale.into_iter().map(|tile| TreeError::StaleGridLocation {
+                container: it,
+                tile,
+            }));
+        }
+    }
+}
@@ -0,0 +1,387 @@
+//! Several named layouts ("workspaces") over the same set of panes.
+
+use super::{
+    ContainerInsertion, ContainerKind, InsertionPoint, SerializedTree, Tile, Tree, TreeV1,
+};
+
+/// An inactive workspace.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct Workspace<Key> {
+    name: String,
+
+    /// Stale for the active workspace; it lives in [`Workspaces::tree`] instead.
+    layout: SerializedTree<Key>,
+
+    /// All the panes that existed when this workspace was last active.
+    ///
+    /// Panes missing from the layout but present here were hidden on purpose,
+    /// while panes missing from both were added while the workspace was inactive.
+    known: Vec<Key>,
+}
+
+/// Several named layouts of the same panes, e.g. a "debug", a "review" and a "presentation" arrangement.
+///
+/// Only one workspace is active at a time, and it is a normal [`Tree`] that you show with [`Tree::ui`].
+/// The panes that are not part of the active workspace are kept alive in the background,
+/// so switching workspaces never re-creates panes.
+///
+/// Panes are identified across workspaces by a `Key` of your choosing, e.g. a name or an id.
+///
+/// When switching to a workspace, its layout is reconciled with the current set of panes:
+/// * Panes that were closed while it was inactive are removed from it.
+/// * Panes that were added while it was inactive are added to it as tabs at the root, and made active.
+/// * Panes that were already around, but not part of its layout, stay hidden.
+///
+/// ```
+/// use egui_tiles::{Tree, Workspaces};
+///
+/// let key = |pane: &String| pane.clone();
+/// let tree = Tree::new_horizontal(vec!["code".to_owned(), "log".to_owned()]);
+/// let mut workspaces = Workspaces::new("debug", tree);
+///
+/// // A copy of the current arrangement that only shows the code:
+/// let layout = Tree::new_tabs(vec!["code".to_owned()]).to_layout(key);
+/// workspaces.add("presentation", layout, key);
+///
+/// assert!(workspaces.switch_to("presentation", key));
+/// assert_eq!(workspaces.tree.to_layout_string(|pane| pane.clone()), "tabs[code]");
+///
+/// workspaces.add_pane("notes".to_owned(), key);
+///
+/// assert!(workspaces.switch_to("debug", key));
+/// assert_eq!(workspaces.tree.to_layout_string(|pane| pane.clone()), "tabs[h[code, log], *notes]");
+/// ```
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Workspaces<Key, Pane> {
+    /// The active workspace.
+    pub tree: Tree<Pane>,
+
+    /// Index into [`Self::workspaces`].
+    active: usize,
+
+    workspaces: Vec<Workspace<Key>>,
+
+    /// Panes not part of the active workspace.
+    hidden: Vec<(Key, Pane)>,
+}
+
+impl<Key: Clone + PartialEq, Pane> Workspaces<Key, Pane> {
+    /// Start out with a single workspace.
+    pub fn new(name: impl Into<String>, tree: Tree<Pane>) -> Self {
+        Self {
+            tree,
+            active: 0,
+            workspaces: vec![Workspace {
+                name: name.into(),
+                layout: empty_layout(),
+                known: vec![],
+            }],
+            hidden: vec![],
+        }
+    }
+
+    /// The name of the active workspace.
+    pub fn active_name(&self) -> &str {
+        &self.workspaces[self.active].name
+    }
+
+    /// The names of all workspaces, in the order they were added.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.workspaces
+            .iter()
+            .map(|workspace| workspace.name.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.index_of(name).is_some()
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.workspaces
+            .iter()
+            .position(|workspace| workspace.name == name)
+    }
+
+    /// Add a new workspace with the given layout, e.g. from [`Tree::to_layout`].
+    ///
+    /// Existing panes that are not part of the layout will be hidden in it.
+    /// If there already is a workspace with the same name, its layout is replaced.
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        layout: SerializedTree<Key>,
+        pane_key: impl FnMut(&Pane) -> Key,
+    ) {
+        let name = name.into();
+        let known = self.pane_keys(pane_key);
+        if let Some(index) = self.index_of(&name) {
+            if index == self.active {
+                log::warn!("Can't replace the layout of the active workspace {name:?}");
+                return;
+            }
+            self.workspaces[index].layout = layout;
+            self.workspaces[index].known = known;
+        } else {
+            self.workspaces.push(Workspace {
+                name,
+                layout,
+                known,
+            });
+        }
+    }
+
+    /// Save the current arrangement as a new workspace, without switching to it.
+    pub fn save_as(&mut self, name: impl Into<String>, mut pane_key: impl FnMut(&Pane) -> Key) {
+        let layout = self.tree.to_layout(&mut pane_key);
+        self.add(name, layout, pane_key);
+    }
+
+    /// Remove an inactive workspace.
+    ///
+    /// Returns `false` if there is no such workspace, or if it is the active one.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let Some(index) = self.index_of(name) else { return false; };
+        if index == self.active {
+            log::warn!("Can't remove the active workspace {name:?}");
+            return false;
+        }
+        self.workspaces.remove(index);
+        if index < self.active {
+            self.active -= 1;
+        }
+        true
+    }
+
+    /// Add a pane to all workspaces.
+    ///
+    /// It is added to the active workspace right away,
+    /// and to the other ones the next time they are switched to.
+    ///
+    /// If there already is a pane with the same key, it is replaced, and returned.
+    /// A replaced pane in the active workspace keeps its place,
+    /// while a replaced hidden pane is brought out like any new pane.
+    pub fn add_pane(&mut self, pane: Pane, mut pane_key: impl FnMut(&Pane) -> Key) -> Option<Pane> {
+        let key = pane_key(&pane);
+
+        let existing = self
+            .tree
+            .tiles
+            .tiles
+            .values_mut()
+            .find_map(|tile| match tile {
+                Tile::Pane(existing) if pane_key(existing) == key => Some(existing),
+                _ => None,
+            });
+        if let Some(existing) = existing {
+            let replaced = std::mem::replace(existing, pane);
+            self.tree.tiles.key_index.invalidate();
+            return Some(replaced);
+        }
+
+        let replaced = self
+            .hidden
+            .iter()
+            .position(|(hidden_key, _)| hidden_key == &key)
+            .map(|index| self.hidden.remove(index).1);
+        self.add_to_active(vec![pane]);
+        replaced
+    }
+
+    /// Remove a pane from all workspaces, returning it.
+    ///
+    /// To remove a pane from only the active workspace, remove it from [`Self::tree`] instead.
+    pub fn remove_pane(
+        &mut self,
+        key: &Key,
+        mut pane_key: impl FnMut(&Pane) -> Key,
+    ) -> Option<Pane> {
+        // So that a new pane with the same key is not taken to be hidden on purpose:
+        for workspace in &mut self.workspaces {
+            workspace.known.retain(|known| known != key);
+        }
+
+        if let Some(index) = self
+            .hidden
+            .iter()
+            .position(|(hidden_key, _)| hidden_key == key)
+        {
+            return Some(self.hidden.remove(index).1);
+        }
+
+        let tile_id = self
+            .tree
+            .tiles
+            .tiles
+            .iter()
+            .find_map(|(&tile_id, tile)| match tile {
+                Tile::Pane(pane) if &pane_key(pane) == key => Some(tile_id),
+                _ => None,
+            })?;
+        self.tree
+            .extract_subtree(tile_id)
+            .tiles
+            .tiles
+            .into_values()
+            .find_map(|tile| match tile {
+                Tile::Pane(pane) => Some(pane),
+                Tile::Container(_) => None,
+            })
+    }
+
+    /// All panes, in all workspaces.
+    pub fn panes(&self) -> impl Iterator<Item = &Pane> + '_ {
+        let visible = self
+            .tree
+            .tiles
+            .tiles
+            .values()
+            .filter_map(|tile| match tile {
+                Tile::Pane(pane) => Some(pane),
+                Tile::Container(_) => None,
+            });
+        visible.chain(self.hidden.iter().map(|(_, pane)| pane))
+    }
+
+    fn pane_keys(&self, pane_key: impl FnMut(&Pane) -> Key) -> Vec<Key> {
+        self.panes().map(pane_key).collect()
+    }
+
+    /// Make the workspace with the given name the active one.
+    ///
+    /// Returns `false` if there is no such workspace.
+    pub fn switch_to(&mut self, name: &str, mut pane_key: impl FnMut(&Pane) -> Key) -> bool {
+        let Some(index) = self.index_of(name) else {
+            log::warn!("Failed to find workspace {name:?}");
+            return false;
+        };
+        if index == self.active {
+            return true;
+        }
+
+        // Put away the active workspace:
+        let layout = self.tree.to_layout(&mut pane_key);
+        let known = self.pane_keys(&mut pane_key);
+        let tree = std::mem::take(&mut self.tree);
+        let id_allocator = tree.tiles.id_allocator;
+        for tile in tree.tiles.tiles.into_values() {
+            if let Tile::Pane(pane) = tile {
+                self.hidden.push((pane_key(&pane), pane));
+            }
+        }
+        let previous = &mut self.workspaces[self.active];
+        previous.layout = layout;
+        previous.known = known;
+
+        // Bring out the new one:
+        self.active = index;
+        let workspace = &mut self.workspaces[index];
+        let layout = std::mem::replace(&mut workspace.layout, empty_layout());
+        let hidden = &mut self.hidden;
+        self.tree = Tree::from_layout(layout, |key| {
+            let index = hidden
+                .iter()
+                .position(|(hidden_key, _)| hidden_key == &key)?;
+            Some(hidden.remove(index).1)
+        });
+        self.tree.tiles.id_allocator = id_allocator;
+
+        let (added, hidden): (Vec<_>, Vec<_>) = std::mem::take(&mut self.hidden)
+            .into_iter()
+            .partition(|(key, _)| !workspace.known.contains(key));
+        self.hidden = hidden;
+        self.add_to_active(added.into_iter().map(|(_, pane)| pane).collect());
+
+        true
+    }
+
+    /// Add the panes as tabs at the root of the active workspace.
+    fn add_to_active(&mut self, panes: Vec<Pane>) {
+        if panes.is_empty() {
+            return;
+        }
+        if let Some(root) = self.tree.root {
+            let num_children = match self.tree.tiles.get(root) {
+                Some(Tile::Container(container)) if container.kind() == ContainerKind::Tabs => {
+                    container.children().len()
+                }
+                _ => 1,
+            };
+            self.tree.insert_panes(
+                panes,
+                InsertionPoint::new(root, ContainerInsertion::Tabs(num_children)),
+            );
+        } else {
+            self.tree = Tree::new_tabs(panes);
+        }
+    }
+}
+
+fn empty_layout<Key>() -> SerializedTree<Key> {
+    SerializedTree::V1(TreeV1 {
+        root: None,
+        tiles: vec![],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::IdAllocator;
+
+    use super::*;
+
+    #[test]
+    fn re_added_pane_is_not_hidden() {
+        let key = |pane: &String| pane.clone();
+        let tree = Tree::new_horizontal(vec!["a".to_owned(), "b".to_owned()]);
+        let mut workspaces = Workspaces::new("both", tree);
+        let layout = Tree::new_tabs(vec!["a".to_owned()]).to_layout(key);
+        workspaces.add("only a", layout, key);
+
+        assert_eq!(
+            workspaces.remove_pane(&"b".to_owned(), key),
+            Some("b".to_owned())
+        );
+        workspaces.add_pane("b".to_owned(), key);
+
+        assert!(workspaces.switch_to("only a", key));
+        assert_eq!(
+            workspaces.tree.to_layout_string(|pane| pane.clone()),
+            "tabs[a, *b]"
+        );
+    }
+
+    #[test]
+    fn adding_a_pane_with_a_used_key_replaces_it() {
+        let key = |pane: &(char, u32)| pane.0;
+        let tree = Tree::new_horizontal(vec![('a', 1), ('b', 1)]);
+        let mut workspaces = Workspaces::new("both", tree);
+        let layout = Tree::new_tabs(vec![('a', 1)]).to_layout(key);
+        workspaces.add("only a", layout, key);
+        assert!(workspaces.switch_to("only a", key));
+
+        // Hidden in the active workspace:
+        assert_eq!(workspaces.add_pane(('b', 2), key), Some(('b', 1)));
+        // Shown in the active workspace:
+        assert_eq!(workspaces.add_pane(('b', 3), key), Some(('b', 2)));
+        assert_eq!(workspaces.add_pane(('c', 1), key), None);
+
+        let mut panes: Vec<_> = workspaces.panes().copied().collect();
+        panes.sort_unstable();
+        assert_eq!(panes, vec![('a', 1), ('b', 3), ('c', 1)]);
+    }
+
+    #[test]
+    fn switching_keeps_the_id_allocator() {
+        let key = |pane: &String| pane.clone();
+        let mut tree = Tree::new_horizontal(vec!["a".to_owned()]);
+        tree.tiles.id_allocator = IdAllocator::Sequential { next: 100 };
+        let mut workspaces = Workspaces::new("first", tree);
+        workspaces.save_as("second", key);
+
+        assert!(workspaces.switch_to("second", key));
+        assert!(matches!(
+            workspaces.tree.tiles.id_allocator,
+            IdAllocator::Sequential { .. }
+        ));
+    }
+}